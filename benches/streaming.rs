@@ -0,0 +1,81 @@
+// Whole-document processing against page-by-page streaming on a synthetic
+// 10 000-block Marker document with embedded image data. Both write the
+// same JSON (see tests/streaming.rs); streaming never holds more than one
+// page, which shows in peak RSS rather than here: compare
+// `/usr/bin/time -v flatten_marker_output big.json` with and without a
+// whole-document flag such as --merge-split-tables.
+use criterion::{Criterion, criterion_group, criterion_main};
+use pdf_parser::reader::{read_document, read_pages};
+use pdf_parser::streaming::BlockWriter;
+use pdf_parser::{Document, OutputFormat, ProcessingConfig, process_document};
+use serde_json::json;
+use std::hint::black_box;
+use std::io::{self, Write};
+
+const PAGES: usize = 500;
+const BLOCKS_PER_PAGE: usize = 20;
+
+// Marker embeds each block's images as base64; 4 KB is a small thumbnail
+fn synthetic_document() -> Vec<u8> {
+    let image = "A".repeat(4096);
+    let pages: Vec<serde_json::Value> = (0..PAGES)
+        .map(|page| {
+            let children: Vec<serde_json::Value> = (0..BLOCKS_PER_PAGE)
+                .map(|index| {
+                    json!({
+                        "id": format!("/page/{}/Text/{}", page, index),
+                        "block_type": if index == 0 { "SectionHeader" } else { "Text" },
+                        "html": format!("<p>Block {} of page {}: fish &amp; <b>chips</b>.</p>", index, page),
+                        "bbox": [72.0, 100.0, 540.0, 120.0],
+                        "images": {"figure.jpeg": image},
+                    })
+                })
+                .collect();
+            json!({
+                "id": format!("/page/{}/Page/0", page),
+                "block_type": "Page",
+                "html": "",
+                "children": children,
+            })
+        })
+        .collect();
+    serde_json::to_vec(&json!({"children": pages})).unwrap()
+}
+
+fn whole_document(input: &[u8], config: &ProcessingConfig, output: impl Write) -> io::Result<()> {
+    let document = read_document(input, false)?;
+    let processed = process_document(document, config);
+    let mut output = output;
+    output.write_all(serde_json::to_string_pretty(&processed.blocks)?.as_bytes())
+}
+
+fn page_by_page(input: &[u8], config: &ProcessingConfig, output: impl Write) -> io::Result<()> {
+    let mut writer = BlockWriter::new(OutputFormat::Json, output).unwrap();
+    read_pages(input, false, |page| {
+        let processed = process_document(Document { children: vec![page] }, config);
+        for block in &processed.blocks {
+            writer.write_block(block)?;
+        }
+        Ok::<(), io::Error>(())
+    })?;
+    writer.finish()?;
+    Ok(())
+}
+
+fn bench_streaming(c: &mut Criterion) {
+    let input = synthetic_document();
+    let config = ProcessingConfig::default();
+
+    let mut group = c.benchmark_group("10000 blocks");
+    group.sample_size(10);
+    group.bench_function("whole document", |b| {
+        b.iter(|| whole_document(black_box(&input), &config, io::sink()).unwrap())
+    });
+    group.bench_function("page by page", |b| {
+        b.iter(|| page_by_page(black_box(&input), &config, io::sink()).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_streaming);
+criterion_main!(benches);
@@ -0,0 +1,249 @@
+use crate::Block;
+use crate::tokenize::is_stopword;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+
+// Abbreviations longer than this are unlikely to be acronyms
+const MAX_ABBREVIATION_LENGTH: usize = 10;
+
+// How many words beyond one per abbreviation letter a long form may span,
+// for function words like "of" or "de" that are skipped in acronyms
+const MAX_EXTRA_WORDS: usize = 3;
+
+struct Definition {
+    // Byte range of `<Long Form> (<ABBREV>)` in the text
+    start: usize,
+    end: usize,
+    abbreviation: String,
+    long_form: String,
+}
+
+/// Finds `<Long Form> (<ABBREV>)` definitions in document order and expands
+/// later standalone uses of each abbreviation in block texts: the first use
+/// after the definition becomes `<Long Form> (<ABBREV>)`, the rest just
+/// `<Long Form>`. Returns the abbreviation → long form map and the number of
+/// uses expanded.
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::abbreviations::expand_abbreviations;
+///
+/// let text = |text: &str| Block { text: text.to_string(), ..Default::default() };
+/// let mut blocks = [
+///     text("We apply machine learning (ML) to parsing."),
+///     text("ML is fast. ML is cheap."),
+/// ];
+/// let (long_forms, expanded) = expand_abbreviations(&mut blocks);
+/// assert_eq!(long_forms["ML"], "machine learning");
+/// assert_eq!(expanded, 2);
+/// assert_eq!(blocks[1].text, "machine learning (ML) is fast. machine learning is cheap.");
+/// ```
+pub fn expand_abbreviations(blocks: &mut [Block]) -> (HashMap<String, String>, usize) {
+    let token_re = Regex::new(r"\b[A-Z][A-Za-z0-9&]*\b").unwrap();
+    let mut long_forms: HashMap<String, String> = HashMap::new();
+    let mut reintroduced: HashSet<String> = HashSet::new();
+    let mut expanded = 0;
+
+    for block in blocks {
+        let text = &block.text;
+        let definitions = find_definitions(text);
+        let mut pending = definitions.iter().peekable();
+        let mut output = String::with_capacity(text.len());
+        let mut last_end = 0;
+
+        for token in token_re.find_iter(text) {
+            while let Some(definition) = pending.next_if(|definition| definition.end <= token.start()) {
+                long_forms
+                    .entry(definition.abbreviation.clone())
+                    .or_insert_with(|| definition.long_form.clone());
+            }
+            let in_definition = definitions
+                .iter()
+                .any(|definition| definition.start <= token.start() && token.end() <= definition.end);
+            if in_definition {
+                continue;
+            }
+            let Some(long_form) = long_forms.get(token.as_str()) else {
+                continue;
+            };
+
+            output.push_str(&text[last_end..token.start()]);
+            if reintroduced.insert(token.as_str().to_string()) {
+                output.push_str(&format!("{} ({})", long_form, token.as_str()));
+            } else {
+                output.push_str(long_form);
+            }
+            last_end = token.end();
+            expanded += 1;
+        }
+        for definition in pending {
+            long_forms
+                .entry(definition.abbreviation.clone())
+                .or_insert_with(|| definition.long_form.clone());
+        }
+
+        if last_end > 0 {
+            output.push_str(&text[last_end..]);
+            block.text = output;
+        }
+    }
+
+    (long_forms, expanded)
+}
+
+// `<Long Form> (<ABBREV>)` where the initials of the words before the
+// parenthesis spell the abbreviation, with or without their function words
+// ("Organización de las Naciones Unidas (ONU)", "Bank of America (BoA)").
+// A trailing lowercase `s` on the abbreviation is taken as a plural.
+fn find_definitions(text: &str) -> Vec<Definition> {
+    let paren_re = Regex::new(r"\(\s*([A-Z][A-Za-z0-9&]*?[A-Z0-9])(s?)\s*\)").unwrap();
+    let word_re = Regex::new(r"[\p{L}\p{N}][\p{L}\p{N}'’-]*").unwrap();
+    let mut definitions = Vec::new();
+
+    for caps in paren_re.captures_iter(text) {
+        let paren = caps.get(0).unwrap();
+        let abbreviation = &caps[1];
+        let letters: String = abbreviation
+            .chars()
+            .filter(|c| c.is_alphabetic())
+            .flat_map(char::to_lowercase)
+            .collect();
+        let uppercase = abbreviation.chars().filter(|c| c.is_uppercase()).count();
+        if abbreviation.len() > MAX_ABBREVIATION_LENGTH || uppercase < 2 || letters.is_empty() {
+            continue;
+        }
+
+        // Words before the parenthesis, nearest last, stopping at the
+        // previous definition or sentence break
+        let search_start = definitions.last().map_or(0, |definition: &Definition| definition.end);
+        let before = &text[search_start..paren.start()];
+        let clause_start = before.rfind(['.', ';', ':', '(', ')']).map_or(0, |i| i + 1);
+        let words: Vec<regex::Match> = word_re.find_iter(&before[clause_start..]).collect();
+        let max_words = letters.chars().count() + MAX_EXTRA_WORDS;
+
+        // The shortest run of words that fits, so leading articles are left out
+        let matched = (1..=words.len().min(max_words)).find(|&count| {
+            let candidate = &words[words.len() - count..];
+            initials(candidate, false) == letters || initials(candidate, true) == letters
+        });
+
+        if let Some(count) = matched {
+            let offset = search_start + clause_start;
+            let first = &words[words.len() - count];
+            let last = &words[words.len() - 1];
+            definitions.push(Definition {
+                start: offset + first.start(),
+                end: paren.end(),
+                abbreviation: format!("{}{}", abbreviation, &caps[2]),
+                long_form: text[offset + first.start()..offset + last.end()].to_string(),
+            });
+        }
+    }
+
+    definitions
+}
+
+fn initials(words: &[regex::Match], skip_stopwords: bool) -> String {
+    words
+        .iter()
+        .map(|word| word.as_str())
+        .filter(|word| !skip_stopwords || !is_stopword(&word.to_lowercase()))
+        .filter_map(|word| word.chars().next())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocks(texts: &[&str]) -> Vec<Block> {
+        texts
+            .iter()
+            .map(|text| Block {
+                text: text.to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn texts(blocks: &[Block]) -> Vec<&str> {
+        blocks.iter().map(|block| block.text.as_str()).collect()
+    }
+
+    #[test]
+    fn later_uses_are_expanded_once_in_full_then_as_the_long_form() {
+        let mut blocks = blocks(&[
+            "We study machine learning (ML) for parsing.",
+            "ML works well here.",
+            "Unlike earlier ML systems, it scales.",
+        ]);
+        let (long_forms, expanded) = expand_abbreviations(&mut blocks);
+        assert_eq!(long_forms["ML"], "machine learning");
+        assert_eq!(expanded, 2);
+        assert_eq!(
+            texts(&blocks),
+            [
+                "We study machine learning (ML) for parsing.",
+                "machine learning (ML) works well here.",
+                "Unlike earlier machine learning systems, it scales.",
+            ]
+        );
+    }
+
+    #[test]
+    fn uses_in_the_defining_block_after_the_definition_are_expanded() {
+        let mut blocks = blocks(&["The World Health Organization (WHO) said the WHO agrees."]);
+        let (_, expanded) = expand_abbreviations(&mut blocks);
+        assert_eq!(expanded, 1);
+        assert_eq!(
+            texts(&blocks),
+            ["The World Health Organization (WHO) said the World Health Organization (WHO) agrees."]
+        );
+    }
+
+    #[test]
+    fn uses_before_the_definition_are_left_alone() {
+        let mut blocks = blocks(&["NLP is everywhere.", "Natural language processing (NLP) is a field."]);
+        let (long_forms, expanded) = expand_abbreviations(&mut blocks);
+        assert_eq!(long_forms["NLP"], "Natural language processing");
+        assert_eq!(expanded, 0);
+        assert_eq!(texts(&blocks)[0], "NLP is everywhere.");
+    }
+
+    #[test]
+    fn function_words_and_plurals_in_definitions() {
+        let definitions = find_definitions("She joined the Bank of America (BoA) in 2001.");
+        assert_eq!(definitions.len(), 1);
+        assert_eq!(definitions[0].abbreviation, "BoA");
+        assert_eq!(definitions[0].long_form, "Bank of America");
+
+        let definitions = find_definitions("La Organización de las Naciones Unidas (ONU) votó.");
+        assert_eq!(definitions[0].long_form, "Organización de las Naciones Unidas");
+
+        let definitions = find_definitions("Use large language models (LLMs) carefully.");
+        assert_eq!(definitions[0].abbreviation, "LLMs");
+        assert_eq!(definitions[0].long_form, "large language models");
+    }
+
+    #[test]
+    fn parentheses_that_are_not_definitions_are_ignored() {
+        assert!(find_definitions("See the results (Table 2) below.").is_empty());
+        assert!(find_definitions("Measured in volts (V) only.").is_empty());
+        assert!(find_definitions("A quick brown fox (XYZ) jumped.").is_empty());
+
+        let mut blocks = blocks(&["No definitions here.", "Just text with XYZ in it."]);
+        let (long_forms, expanded) = expand_abbreviations(&mut blocks);
+        assert!(long_forms.is_empty());
+        assert_eq!(expanded, 0);
+    }
+
+    #[test]
+    fn only_standalone_uses_are_expanded() {
+        let mut blocks = blocks(&["Graphics processing units (GPU) are fast.", "GPUs and GPU-bound MLGPU work."]);
+        expand_abbreviations(&mut blocks);
+        assert_eq!(
+            texts(&blocks)[1],
+            "GPUs and Graphics processing units (GPU)-bound MLGPU work."
+        );
+    }
+}
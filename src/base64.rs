@@ -0,0 +1,139 @@
+// Base64 (RFC 4648 standard alphabet, with padding) for block fields that are
+// embedded in HTML `data-*` attributes
+use crate::Block;
+use ::base64::Engine;
+use ::base64::engine::general_purpose::STANDARD;
+use clap::ValueEnum;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Base64Field {
+    Id,
+    BlockType,
+    Html,
+    Text,
+}
+
+impl Base64Field {
+    fn label(self) -> &'static str {
+        match self {
+            Base64Field::Id => "id",
+            Base64Field::BlockType => "block_type",
+            Base64Field::Html => "html",
+            Base64Field::Text => "text",
+        }
+    }
+
+    fn value_mut(self, block: &mut Block) -> &mut String {
+        match self {
+            Base64Field::Id => &mut block.id,
+            Base64Field::BlockType => &mut block.block_type,
+            Base64Field::Html => &mut block.html,
+            Base64Field::Text => &mut block.text,
+        }
+    }
+}
+
+/// Replaces the selected fields of every block (and its children) with their
+/// base64 encoding
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::base64::{self, Base64Field};
+///
+/// let mut blocks = [Block { text: "Hi".to_string(), ..Default::default() }];
+/// base64::encode_blocks(&mut blocks, &[Base64Field::Text]);
+/// assert_eq!(blocks[0].text, "SGk=");
+/// ```
+pub fn encode_blocks(blocks: &mut [Block], fields: &[Base64Field]) {
+    for block in blocks {
+        for &field in fields {
+            let value = field.value_mut(block);
+            *value = STANDARD.encode(value.as_bytes());
+        }
+        if let Some(children) = &mut block.children {
+            encode_blocks(children, fields);
+        }
+    }
+}
+
+/// Reverses encode_blocks. Fails on the first field that is not valid base64
+/// or does not decode to UTF-8.
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::base64::{self, Base64Field};
+///
+/// let mut blocks = [Block { text: "SGk=".to_string(), ..Default::default() }];
+/// base64::decode_blocks(&mut blocks, &[Base64Field::Text]).unwrap();
+/// assert_eq!(blocks[0].text, "Hi");
+///
+/// blocks[0].text = "not base64".to_string();
+/// assert!(base64::decode_blocks(&mut blocks, &[Base64Field::Text]).is_err());
+/// ```
+pub fn decode_blocks(blocks: &mut [Block], fields: &[Base64Field]) -> Result<(), String> {
+    for block in blocks {
+        let id = block.id.clone();
+        for &field in fields {
+            let value = field.value_mut(block);
+            let decoded = STANDARD
+                .decode(value.as_bytes())
+                .map_err(|e| e.to_string())
+                .and_then(|bytes| String::from_utf8(bytes).map_err(|_| "not valid UTF-8".to_string()))
+                .map_err(|e| format!("block {:?}: cannot decode base64 {}: {}", id, field.label(), e))?;
+            *value = decoded;
+        }
+        if let Some(children) = &mut block.children {
+            decode_blocks(children, fields)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_fields_round_trip_including_children() {
+        let original = vec![Block {
+            id: "/page/0/ListGroup/0".to_string(),
+            block_type: "ListGroup".to_string(),
+            html: "<ul><li>ñ</li></ul>".to_string(),
+            text: "• ñ 日本".to_string(),
+            children: Some(vec![Block {
+                block_type: "ListItem".to_string(),
+                text: "ñ".to_string(),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        }];
+        let fields = [Base64Field::Text, Base64Field::BlockType];
+
+        let mut blocks = original.clone();
+        encode_blocks(&mut blocks, &fields);
+        assert_eq!(blocks[0].text, "4oCiIMOxIOaXpeacrA==");
+        assert_eq!(blocks[0].block_type, "TGlzdEdyb3Vw");
+        assert_eq!(blocks[0].html, original[0].html);
+        assert_eq!(blocks[0].children.as_ref().unwrap()[0].text, "w7E=");
+
+        decode_blocks(&mut blocks, &fields).unwrap();
+        assert_eq!(
+            serde_json::to_value(&blocks).unwrap(),
+            serde_json::to_value(&original).unwrap()
+        );
+    }
+
+    #[test]
+    fn decoding_names_the_failing_block_and_field() {
+        let mut blocks = vec![Block {
+            id: "/page/2/Text/4".to_string(),
+            text: "not base64!".to_string(),
+            ..Default::default()
+        }];
+        let error = decode_blocks(&mut blocks, &[Base64Field::Text]).unwrap_err();
+        assert!(error.contains("/page/2/Text/4") && error.contains("text"), "{}", error);
+
+        // Valid base64 of bytes that are not UTF-8
+        blocks[0].text = "//4=".to_string();
+        let error = decode_blocks(&mut blocks, &[Base64Field::Text]).unwrap_err();
+        assert!(error.contains("not valid UTF-8"), "{}", error);
+    }
+}
@@ -0,0 +1,204 @@
+use crate::{Block, extract_text_from_html};
+use regex::Regex;
+use std::collections::HashSet;
+
+// How far (in points) a block's left edge must sit to the right of the
+// document's median text margin to count as visually indented
+const INDENT_THRESHOLD: f64 = 24.0;
+
+/// Finds `Text` blocks that are really quotations. A block qualifies if:
+/// - its text is wrapped in quotation marks, optionally followed by a
+///   `— Author` attribution,
+/// - its left edge is indented well past the median left margin of the
+///   document's text blocks, or
+/// - the preceding text block introduces it (`... wrote:`, `... dijo:`).
+///
+/// Runs on the raw Marker blocks because bboxes are stripped during
+/// flattening. Returns the ids of the blocks to relabel as `BlockQuote`.
+/// ```
+/// use pdf_parser::{Block, blockquote};
+///
+/// let text = |id: &str, html: &str| Block {
+///     id: id.to_string(),
+///     block_type: "Text".to_string(),
+///     html: html.to_string(),
+///     ..Default::default()
+/// };
+/// let blocks = [
+///     text("/page/0/Text/0", "<p>As the author wrote:</p>"),
+///     text("/page/0/Text/1", "<p>Brevity is the soul of wit.</p>"),
+///     text("/page/0/Text/2", "<p>Back to the argument.</p>"),
+/// ];
+/// let quotes = blockquote::detect_blockquotes(&blocks);
+/// assert!(quotes.contains("/page/0/Text/1"));
+/// assert_eq!(quotes.len(), 1);
+/// ```
+pub fn detect_blockquotes(blocks: &[Block]) -> HashSet<String> {
+    let mut text_blocks = Vec::new();
+    collect_text_blocks(blocks, &mut text_blocks);
+
+    let median_left = median(
+        text_blocks
+            .iter()
+            .filter_map(|block| left_edge(block))
+            .collect(),
+    );
+    let quoted_re =
+        Regex::new(r#"^["“«„](?s:.+)["”»]\s*(?:[—–-]\s*[^"“”«»]{1,80})?$"#).unwrap();
+    let introduction_re = Regex::new(
+        r"(?i)\b(?:wrote|writes|said|says|stated|states|escribió|escribe|dijo|dice|declaró|afirmó)\s*:\s*$",
+    )
+    .unwrap();
+
+    let mut quotes = HashSet::new();
+    let mut previous_text = String::new();
+    for block in text_blocks {
+        let text = extract_text_from_html(&block.html);
+
+        let quoted = quoted_re.is_match(&text);
+        let indented = match (left_edge(block), median_left) {
+            (Some(left), Some(median)) => left - median > INDENT_THRESHOLD,
+            _ => false,
+        };
+        let introduced = introduction_re.is_match(&previous_text);
+
+        if quoted || indented || introduced {
+            quotes.insert(block.id.clone());
+        }
+        previous_text = text;
+    }
+    quotes
+}
+
+/// Changes the type of the `Text` blocks among `quote_ids` to `BlockQuote`
+/// ```
+/// use pdf_parser::{Block, blockquote};
+/// use std::collections::HashSet;
+///
+/// let mut blocks = [Block {
+///     id: "/page/0/Text/1".to_string(),
+///     block_type: "Text".to_string(),
+///     ..Default::default()
+/// }];
+/// blockquote::relabel_blockquotes(&mut blocks, &HashSet::from(["/page/0/Text/1".to_string()]));
+/// assert_eq!(blocks[0].block_type, "BlockQuote");
+/// ```
+pub fn relabel_blockquotes(blocks: &mut [Block], quote_ids: &HashSet<String>) {
+    for block in blocks {
+        if block.block_type == "Text" && quote_ids.contains(&block.id) {
+            block.block_type = "BlockQuote".to_string();
+        }
+    }
+}
+
+fn collect_text_blocks<'a>(blocks: &'a [Block], result: &mut Vec<&'a Block>) {
+    for block in blocks {
+        if block.block_type == "Text" {
+            result.push(block);
+        }
+        if let Some(children) = &block.children {
+            collect_text_blocks(children, result);
+        }
+    }
+}
+
+fn left_edge(block: &Block) -> Option<f64> {
+    block.bbox.as_ref().and_then(|bbox| bbox.first().copied())
+}
+
+fn median(mut values: Vec<f64>) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        Some((values[mid - 1] + values[mid]) / 2.0)
+    } else {
+        Some(values[mid])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(id: &str, html: &str, left: f64) -> Block {
+        Block {
+            id: id.to_string(),
+            block_type: "Text".to_string(),
+            html: html.to_string(),
+            bbox: Some(vec![left, 100.0, 500.0, 140.0]),
+            ..Default::default()
+        }
+    }
+
+    fn page(children: Vec<Block>) -> Vec<Block> {
+        vec![Block {
+            id: "/page/0/Page/0".to_string(),
+            block_type: "Page".to_string(),
+            children: Some(children),
+            ..Default::default()
+        }]
+    }
+
+    fn detected(blocks: &[Block]) -> Vec<String> {
+        let mut ids: Vec<String> = detect_blockquotes(blocks).into_iter().collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn quoted_text_with_or_without_attribution() {
+        let blocks = page(vec![
+            text("a", "<p>“Stay hungry, stay foolish.” — Stewart Brand</p>", 72.0),
+            text("b", "<p>«La vida es sueño»</p>", 72.0),
+            text("c", "<p>He called it \"a start\" and left.</p>", 72.0),
+        ]);
+        assert_eq!(detected(&blocks), ["a", "b"]);
+    }
+
+    #[test]
+    fn blocks_indented_past_the_median_margin() {
+        let blocks = page(vec![
+            text("a", "<p>Body paragraph one.</p>", 72.0),
+            text("b", "<p>Body paragraph two.</p>", 72.0),
+            text("c", "<p>Set-off passage.</p>", 110.0),
+            text("d", "<p>Slightly indented first line.</p>", 90.0),
+        ]);
+        assert_eq!(detected(&blocks), ["c"]);
+    }
+
+    #[test]
+    fn blocks_introduced_by_the_preceding_text() {
+        let blocks = page(vec![
+            text("a", "<p>In her letter she wrote:</p>", 72.0),
+            text("b", "<p>The harvest failed again this year.</p>", 72.0),
+            text("c", "<p>Y el alcalde dijo :</p>", 72.0),
+            text("d", "<p>No habrá fiesta.</p>", 72.0),
+            text("e", "<p>Nothing else was said.</p>", 72.0),
+        ]);
+        assert_eq!(detected(&blocks), ["b", "d"]);
+    }
+
+    #[test]
+    fn only_text_blocks_are_considered_and_relabeled() {
+        let mut header = text("h", "<h1>“Quoted title”</h1>", 72.0);
+        header.block_type = "SectionHeader".to_string();
+        let blocks = page(vec![header.clone(), text("q", "<p>“Quoted”</p>", 72.0)]);
+        let ids = detect_blockquotes(&blocks);
+        assert_eq!(ids.len(), 1);
+
+        let mut flat = vec![header, text("q", "<p>“Quoted”</p>", 72.0)];
+        relabel_blockquotes(&mut flat, &ids);
+        assert_eq!(flat[0].block_type, "SectionHeader");
+        assert_eq!(flat[1].block_type, "BlockQuote");
+    }
+
+    #[test]
+    fn median_of_odd_even_and_empty_lists() {
+        assert_eq!(median(vec![3.0, 1.0, 2.0]), Some(2.0));
+        assert_eq!(median(vec![4.0, 1.0, 2.0, 3.0]), Some(2.5));
+        assert_eq!(median(Vec::new()), None);
+    }
+}
@@ -0,0 +1,373 @@
+// Detection of the points where one source document ends and the next
+// begins in a Marker JSON file that combines several PDFs.
+//
+// Cues, checked on the raw Marker pages since flattening drops page
+// headers, footers and bboxes:
+// - the printed page number in a PageHeader/PageFooter resets to 1,
+// - the page size changes, which rarely happens within one PDF,
+// - a SectionHeader follows a vertical gap covering a large part of the page.
+use crate::{Block, extract_text_from_html, page_from_id};
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+// Fraction of the page height a gap before a heading must exceed
+const MIN_GAP_RATIO: f64 = 0.4;
+
+// Page dimensions (in points) that differ by less than this are the same size
+const PAGE_SIZE_TOLERANCE: f64 = 2.0;
+
+pub const BOUNDARY_BLOCK_TYPE: &str = "DocumentBoundary";
+
+// A page number printed on its own in a page header or footer ("7",
+// "- 7 -", "Page 7", "Página 7")
+static PAGE_NUMBER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^[\s\-–—|]*(?:p(?:age|ágina|ag\.|\.)\s*)?(\d{1,4})[\s\-–—|]*$").unwrap()
+});
+
+// Boundaries found in a file, along with the position of every page child
+// so processed blocks can be placed relative to them by id
+#[derive(Debug, Default)]
+pub struct Boundaries {
+    boundaries: Vec<DocumentBoundary>,
+    positions: HashMap<String, usize>,
+}
+
+impl Boundaries {
+    /// Number of boundaries found
+    pub fn len(&self) -> usize {
+        self.boundaries.len()
+    }
+
+    /// Whether no boundary was found
+    pub fn is_empty(&self) -> bool {
+        self.boundaries.is_empty()
+    }
+}
+
+#[derive(Debug)]
+struct DocumentBoundary {
+    // Position, among the children of all pages in document order, of the
+    // first block of the new document
+    position: usize,
+    page: u32,
+    reason: String,
+}
+
+/// Finds the boundaries in the raw Marker blocks of a combined file
+/// ```
+/// use pdf_parser::boundaries::{detect_boundaries, insert_boundaries, split_at_boundaries};
+/// use pdf_parser::{Block, FilterConfig, flatten_and_filter_blocks};
+///
+/// let page = |index: usize, width: f64, height: f64| Block {
+///     id: format!("/page/{}/Page/0", index),
+///     block_type: "Page".to_string(),
+///     bbox: Some(vec![0.0, 0.0, width, height]),
+///     children: Some(vec![Block {
+///         id: format!("/page/{}/Text/0", index),
+///         block_type: "Text".to_string(),
+///         html: "<p>Body</p>".to_string(),
+///         bbox: Some(vec![72.0, 100.0, 540.0, 140.0]),
+///         ..Default::default()
+///     }]),
+///     ..Default::default()
+/// };
+/// // A Letter-sized report followed by an A4 one
+/// let pages = vec![page(0, 612.0, 792.0), page(1, 612.0, 792.0), page(2, 595.0, 842.0)];
+/// let boundaries = detect_boundaries(&pages);
+/// assert_eq!(boundaries.len(), 1);
+/// assert!(detect_boundaries(&pages[..2]).is_empty());
+///
+/// // Processed blocks get a DocumentBoundary block where the A4 report starts
+/// let blocks = insert_boundaries(flatten_and_filter_blocks(pages, &FilterConfig::default()), &boundaries);
+/// let types: Vec<&str> = blocks.iter().map(|block| block.block_type.as_str()).collect();
+/// assert_eq!(types, ["Text", "Text", "DocumentBoundary", "Text"]);
+/// assert_eq!(split_at_boundaries(&blocks).iter().map(Vec::len).collect::<Vec<_>>(), [2, 1]);
+/// ```
+pub fn detect_boundaries(blocks: &[Block]) -> Boundaries {
+    let mut boundaries = Vec::new();
+    let mut positions = HashMap::new();
+    let mut position = 0;
+    let mut previous_number: Option<u32> = None;
+    let mut previous_size: Option<(f64, f64)> = None;
+
+    for (page_index, page) in blocks.iter().filter(|block| block.block_type == "Page").enumerate() {
+        let children = page.children.as_deref().unwrap_or(&[]);
+        let page_number = page_from_id(&page.id).unwrap_or(page_index as u32);
+        let printed_number = printed_page_number(children);
+        let size = page.bbox.as_deref().and_then(bbox_size);
+
+        // The first page always starts a document, so only later pages are checked
+        let page_reason = if page_index == 0 {
+            None
+        } else if printed_number == Some(1) && previous_number.is_some_and(|number| number > 1) {
+            Some("page numbering restarts at 1".to_string())
+        } else if let (Some((width, height)), Some((previous_width, previous_height))) = (size, previous_size)
+            && ((width - previous_width).abs() > PAGE_SIZE_TOLERANCE
+                || (height - previous_height).abs() > PAGE_SIZE_TOLERANCE)
+        {
+            Some(format!(
+                "page size changes from {:.0}x{:.0} to {:.0}x{:.0}",
+                previous_width, previous_height, width, height
+            ))
+        } else {
+            None
+        };
+
+        if let Some(reason) = page_reason {
+            boundaries.push(DocumentBoundary {
+                position,
+                page: page_number,
+                reason,
+            });
+        } else if let Some(offset) = gap_before_heading(children, size) {
+            boundaries.push(DocumentBoundary {
+                position: position + offset,
+                page: page_number,
+                reason: "large vertical gap before a heading".to_string(),
+            });
+        }
+
+        if printed_number.is_some() {
+            previous_number = printed_number;
+        }
+        if size.is_some() {
+            previous_size = size;
+        }
+        for child in children {
+            positions.insert(child.id.clone(), position);
+            position += 1;
+        }
+    }
+
+    Boundaries { boundaries, positions }
+}
+
+/// Inserts a DocumentBoundary block before the first processed block at or
+/// after each boundary (see detect_boundaries for an example)
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::boundaries::{Boundaries, insert_boundaries};
+///
+/// let blocks = vec![Block { block_type: "Text".to_string(), ..Default::default() }];
+/// assert_eq!(insert_boundaries(blocks, &Boundaries::default()).len(), 1);
+/// ```
+pub fn insert_boundaries(blocks: Vec<Block>, boundaries: &Boundaries) -> Vec<Block> {
+    if boundaries.is_empty() {
+        return blocks;
+    }
+
+    let mut result = Vec::with_capacity(blocks.len() + boundaries.len());
+    let mut pending = boundaries.boundaries.iter().enumerate().peekable();
+    let mut last_position = 0;
+    for block in blocks {
+        // Blocks without a raw counterpart stay with the block before them
+        let position = boundaries.positions.get(&block.id).copied().unwrap_or(last_position);
+        last_position = position;
+        while let Some((index, boundary)) = pending.next_if(|(_, boundary)| boundary.position <= position) {
+            result.push(boundary_block(index, boundary));
+        }
+        result.push(block);
+    }
+    result
+}
+
+/// Splits processed blocks at each DocumentBoundary block, dropping the
+/// boundary blocks themselves
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::boundaries::{BOUNDARY_BLOCK_TYPE, split_at_boundaries};
+///
+/// let block = |block_type: &str| Block { block_type: block_type.to_string(), ..Default::default() };
+/// let documents = split_at_boundaries(&[block("Text"), block("Table"), block(BOUNDARY_BLOCK_TYPE), block("Text")]);
+/// assert_eq!(documents.iter().map(Vec::len).collect::<Vec<_>>(), [2, 1]);
+/// ```
+pub fn split_at_boundaries(blocks: &[Block]) -> Vec<Vec<Block>> {
+    let mut documents = vec![Vec::new()];
+    for block in blocks {
+        if block.block_type == BOUNDARY_BLOCK_TYPE {
+            documents.push(Vec::new());
+        } else {
+            documents.last_mut().unwrap().push(block.clone());
+        }
+    }
+    documents.retain(|document| !document.is_empty());
+    documents
+}
+
+fn boundary_block(index: usize, boundary: &DocumentBoundary) -> Block {
+    Block {
+        id: format!("/page/{}/{}/{}", boundary.page, BOUNDARY_BLOCK_TYPE, index),
+        block_type: BOUNDARY_BLOCK_TYPE.to_string(),
+        html: "<hr>".to_string(),
+        text: format!("Document boundary: {}", boundary.reason),
+        ..Default::default()
+    }
+}
+
+// The first page number printed in a page header or footer among `children`
+fn printed_page_number(children: &[Block]) -> Option<u32> {
+    children
+        .iter()
+        .filter(|block| block.block_type == "PageHeader" || block.block_type == "PageFooter")
+        .find_map(|block| {
+            let text = extract_text_from_html(&block.html);
+            PAGE_NUMBER_RE.captures(&text)?[1].parse().ok()
+        })
+}
+
+// Index among `children` of a SectionHeader preceded by a vertical gap of
+// more than MIN_GAP_RATIO of the page height
+fn gap_before_heading(children: &[Block], page_size: Option<(f64, f64)>) -> Option<usize> {
+    let (_, page_height) = page_size?;
+    let mut previous_bottom: Option<f64> = None;
+
+    for (index, block) in children.iter().enumerate() {
+        if matches!(block.block_type.as_str(), "PageHeader" | "PageFooter") {
+            continue;
+        }
+        let Some(bbox) = block.bbox.as_deref().filter(|bbox| bbox.len() == 4) else {
+            continue;
+        };
+        if block.block_type == "SectionHeader"
+            && let Some(bottom) = previous_bottom
+            && bbox[1] - bottom > page_height * MIN_GAP_RATIO
+        {
+            return Some(index);
+        }
+        previous_bottom = Some(previous_bottom.map_or(bbox[3], |bottom| bottom.max(bbox[3])));
+    }
+    None
+}
+
+fn bbox_size(bbox: &[f64]) -> Option<(f64, f64)> {
+    match bbox {
+        [x0, y0, x1, y1] => Some((x1 - x0, y1 - y0)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LETTER: [f64; 4] = [0.0, 0.0, 612.0, 792.0];
+    const A4: [f64; 4] = [0.0, 0.0, 595.0, 842.0];
+
+    fn child(page: usize, index: usize, block_type: &str, html: &str, bbox: [f64; 4]) -> Block {
+        Block {
+            id: format!("/page/{}/{}/{}", page, block_type, index),
+            block_type: block_type.to_string(),
+            html: html.to_string(),
+            bbox: Some(bbox.to_vec()),
+            ..Default::default()
+        }
+    }
+
+    fn page(index: usize, bbox: [f64; 4], children: Vec<Block>) -> Block {
+        Block {
+            id: format!("/page/{}/Page/0", index),
+            block_type: "Page".to_string(),
+            bbox: Some(bbox.to_vec()),
+            children: Some(children),
+            ..Default::default()
+        }
+    }
+
+    fn text(page: usize, index: usize) -> Block {
+        child(page, index, "Text", "<p>Body</p>", [72.0, 100.0, 540.0, 140.0])
+    }
+
+    fn footer(page: usize, number: &str) -> Block {
+        child(page, 9, "PageFooter", &format!("<p>{}</p>", number), [300.0, 760.0, 320.0, 780.0])
+    }
+
+    fn reasons(blocks: &[Block]) -> Vec<String> {
+        let boundaries = detect_boundaries(blocks);
+        boundaries.boundaries.iter().map(|boundary| boundary.reason.clone()).collect()
+    }
+
+    #[test]
+    fn page_numbering_restarting_at_one() {
+        let blocks = [
+            page(0, LETTER, vec![text(0, 0), footer(0, "1")]),
+            page(1, LETTER, vec![text(1, 0), footer(1, "- 2 -")]),
+            page(2, LETTER, vec![text(2, 0), footer(2, "Página 1")]),
+        ];
+        assert_eq!(reasons(&blocks), ["page numbering restarts at 1"]);
+        assert_eq!(detect_boundaries(&blocks).boundaries[0].page, 2);
+    }
+
+    #[test]
+    fn page_size_changes() {
+        let blocks = [
+            page(0, LETTER, vec![text(0, 0)]),
+            page(1, [0.0, 0.0, 612.5, 791.0], vec![text(1, 0)]),
+            page(2, A4, vec![text(2, 0)]),
+        ];
+        assert_eq!(reasons(&blocks), ["page size changes from 612x791 to 595x842"]);
+    }
+
+    #[test]
+    fn heading_after_a_large_vertical_gap() {
+        let blocks = [
+            page(0, LETTER, vec![text(0, 0)]),
+            page(
+                1,
+                LETTER,
+                vec![
+                    child(1, 0, "Text", "<p>End of report</p>", [72.0, 72.0, 540.0, 120.0]),
+                    child(1, 1, "SectionHeader", "<h1>Appendix</h1>", [72.0, 500.0, 540.0, 530.0]),
+                    child(1, 2, "SectionHeader", "<h2>Close heading</h2>", [72.0, 540.0, 540.0, 560.0]),
+                ],
+            ),
+        ];
+        let boundaries = detect_boundaries(&blocks);
+        assert_eq!(boundaries.len(), 1);
+        assert_eq!(boundaries.boundaries[0].position, 2);
+        assert_eq!(boundaries.boundaries[0].reason, "large vertical gap before a heading");
+    }
+
+    #[test]
+    fn a_single_document_has_no_boundaries() {
+        let blocks = [
+            page(0, LETTER, vec![text(0, 0), footer(0, "1")]),
+            page(1, LETTER, vec![text(1, 0), footer(1, "2")]),
+        ];
+        assert!(detect_boundaries(&blocks).is_empty());
+    }
+
+    #[test]
+    fn boundaries_are_inserted_before_the_first_block_and_split_on() {
+        let blocks = [
+            page(0, LETTER, vec![text(0, 0), text(0, 1)]),
+            page(1, A4, vec![child(1, 0, "PageHeader", "<p>Header</p>", [0.0, 0.0, 1.0, 1.0]), text(1, 1)]),
+        ];
+        let boundaries = detect_boundaries(&blocks);
+
+        // The page header the boundary points at was filtered out
+        let processed = vec![text(0, 0), text(0, 1), text(1, 1)];
+        let with_boundaries = insert_boundaries(processed, &boundaries);
+        let ids: Vec<&str> = with_boundaries.iter().map(|block| block.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            ["/page/0/Text/0", "/page/0/Text/1", "/page/1/DocumentBoundary/0", "/page/1/Text/1"]
+        );
+
+        let documents = split_at_boundaries(&with_boundaries);
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0].len(), 2);
+        assert_eq!(documents[1][0].id, "/page/1/Text/1");
+    }
+
+    #[test]
+    fn splitting_drops_empty_documents() {
+        let boundary = Block {
+            block_type: BOUNDARY_BLOCK_TYPE.to_string(),
+            ..Default::default()
+        };
+        let documents = split_at_boundaries(&[boundary.clone(), text(0, 0), boundary]);
+        assert_eq!(documents.len(), 1);
+        assert!(split_at_boundaries(&[]).is_empty());
+    }
+}
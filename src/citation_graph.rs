@@ -0,0 +1,249 @@
+use crate::corpus::{find_processed_files, read_processed_blocks};
+use pdf_parser::tokenize::tokenize;
+use glob::glob;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Node-link graph in the layout read by D3 and `networkx.node_link_graph`
+#[derive(Serialize, Debug)]
+pub struct CitationGraph {
+    pub directed: bool,
+    pub multigraph: bool,
+    pub nodes: Vec<GraphNode>,
+    pub links: Vec<GraphLink>,
+    // Files that could not be read, with the reason, and citations that
+    // matched no document. Reported by the caller, not written to the graph.
+    #[serde(skip)]
+    pub skipped: Vec<(PathBuf, String)>,
+    #[serde(skip)]
+    pub unmatched_citations: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GraphNode {
+    pub id: String,
+    pub title: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GraphLink {
+    pub source: String,
+    pub target: String,
+    pub citation: String,
+    pub similarity: f64,
+}
+
+// A citations file is either a list of strings or a list of objects with a
+// `title` (or, failing that, `text`) field
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum Citation {
+    Plain(String),
+    Structured {
+        #[serde(default)]
+        title: Option<String>,
+        #[serde(default)]
+        text: Option<String>,
+    },
+}
+
+impl Citation {
+    fn title(&self) -> Option<&str> {
+        match self {
+            Citation::Plain(title) => Some(title),
+            Citation::Structured { title, text } => title.as_deref().or(text.as_deref()),
+        }
+    }
+}
+
+struct CorpusDocument {
+    id: String,
+    title: String,
+    tokens: HashSet<String>,
+}
+
+// Builds a directed graph from every `{stem}_citations.json` below `dir`,
+// linking a document to each corpus document whose title is the closest
+// match (by Jaccard similarity of title tokens) to one of its citations.
+// Matches below `min_similarity` are discarded.
+pub fn build_citation_graph(
+    dir: &Path,
+    min_similarity: f64,
+) -> Result<CitationGraph, Box<dyn std::error::Error>> {
+    let mut documents = Vec::new();
+    let mut skipped = Vec::new();
+    for path in find_processed_files(dir)? {
+        let id = document_id(dir, &path, "_processed");
+        let blocks = match read_processed_blocks(&path) {
+            Ok(blocks) => blocks,
+            Err(e) => {
+                skipped.push((path, e.to_string()));
+                continue;
+            }
+        };
+        let title = blocks
+            .iter()
+            .find(|block| block.block_type == "Title" || block.block_type == "SectionHeader")
+            .map(|block| block.text.clone())
+            .unwrap_or_else(|| id.clone());
+        let tokens = tokenize(&title).into_iter().collect();
+        documents.push(CorpusDocument { id, title, tokens });
+    }
+
+    let mut links = Vec::new();
+    let mut unmatched = 0;
+    let citations_pattern = format!("{}/**/*_citations.json", dir.display());
+    for entry in glob(&citations_pattern)? {
+        let path = entry?;
+        let source = document_id(dir, &path, "_citations");
+        let citations: Vec<Citation> = match serde_json::from_str(&fs::read_to_string(&path)?) {
+            Ok(citations) => citations,
+            Err(e) => {
+                skipped.push((path, e.to_string()));
+                continue;
+            }
+        };
+
+        for citation in citations.iter().filter_map(Citation::title) {
+            let tokens: HashSet<String> = tokenize(citation).into_iter().collect();
+            let best = documents
+                .iter()
+                .filter(|document| document.id != source)
+                .map(|document| (document, jaccard_similarity(&tokens, &document.tokens)))
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+
+            match best {
+                Some((document, similarity)) if similarity >= min_similarity => {
+                    links.push(GraphLink {
+                        source: source.clone(),
+                        target: document.id.clone(),
+                        citation: citation.to_string(),
+                        similarity,
+                    })
+                }
+                _ => unmatched += 1,
+            }
+        }
+    }
+
+    let nodes = documents
+        .into_iter()
+        .map(|document| GraphNode {
+            id: document.id,
+            title: document.title,
+        })
+        .collect();
+
+    Ok(CitationGraph {
+        directed: true,
+        multigraph: false,
+        nodes,
+        links,
+        skipped,
+        unmatched_citations: unmatched,
+    })
+}
+
+pub fn jaccard_similarity(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+// Identifies a document by its path relative to `dir`, without the file
+// suffix, so `a/report_processed.json` and `a/report_citations.json` both
+// map to `a/report` while `b/report_processed.json` stays a separate node
+fn document_id(dir: &Path, path: &Path, suffix: &str) -> String {
+    let stem = crate::file_stem(path);
+    let stem = stem.strip_suffix(suffix).unwrap_or(stem);
+
+    let relative_dir = path
+        .parent()
+        .and_then(|parent| parent.strip_prefix(dir).ok())
+        .unwrap_or(Path::new(""));
+    let mut parts: Vec<String> = relative_dir
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    parts.push(stem.to_string());
+    parts.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    fn processed(title: &str) -> String {
+        serde_json::json!([{ "id": "/page/0/Title/0", "block_type": "Title", "html": "", "text": title }])
+            .to_string()
+    }
+
+    #[test]
+    fn documents_with_the_same_stem_in_different_directories_are_separate_nodes() {
+        let dir = tempfile::tempdir().unwrap();
+        write(&dir.path().join("a/report_processed.json"), &processed("Annual Report on Rivers"));
+        write(&dir.path().join("b/report_processed.json"), &processed("Deep Learning for Flood Prediction"));
+        write(
+            &dir.path().join("a/report_citations.json"),
+            r#"["Deep learning for flood prediction", {"title": "Unknown work"}]"#,
+        );
+
+        let graph = build_citation_graph(dir.path(), 0.5).unwrap();
+        let ids: Vec<&str> = graph.nodes.iter().map(|node| node.id.as_str()).collect();
+        assert_eq!(ids, ["a/report", "b/report"]);
+
+        assert_eq!(graph.links.len(), 1);
+        assert_eq!(graph.links[0].source, "a/report");
+        assert_eq!(graph.links[0].target, "b/report");
+        assert_eq!(graph.links[0].similarity, 1.0);
+        assert_eq!(graph.unmatched_citations, 1);
+    }
+
+    #[test]
+    fn unreadable_files_are_skipped_and_returned() {
+        let dir = tempfile::tempdir().unwrap();
+        write(&dir.path().join("x_processed.json"), &processed("Soil Chemistry Basics"));
+        write(&dir.path().join("y_processed.json"), "not json");
+        write(&dir.path().join("x_citations.json"), r#"{"not": "a list"}"#);
+
+        let graph = build_citation_graph(dir.path(), 0.5).unwrap();
+        assert_eq!(graph.nodes.len(), 1);
+        let skipped: Vec<&Path> = graph.skipped.iter().map(|(path, _)| path.as_path()).collect();
+        assert_eq!(skipped, [dir.path().join("y_processed.json"), dir.path().join("x_citations.json")]);
+        assert!(graph.skipped.iter().all(|(_, reason)| !reason.is_empty()));
+
+        let json = serde_json::to_value(&graph).unwrap();
+        assert!(json.get("skipped").is_none() && json.get("unmatched_citations").is_none());
+    }
+
+    #[test]
+    fn documents_never_cite_themselves_and_weak_matches_are_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        write(&dir.path().join("x_processed.json"), &processed("Soil Chemistry Basics"));
+        write(&dir.path().join("y_processed.json"), &processed("Ocean Currents"));
+        write(&dir.path().join("x_citations.json"), r#"["Soil chemistry basics", "Ocean waves and tides"]"#);
+
+        let graph = build_citation_graph(dir.path(), 0.5).unwrap();
+        assert!(graph.links.is_empty());
+
+        let graph = build_citation_graph(dir.path(), 0.2).unwrap();
+        assert_eq!(graph.links.len(), 1);
+        assert_eq!(graph.links[0].target, "y");
+    }
+
+    #[test]
+    fn jaccard_similarity_of_token_sets() {
+        let set = |words: &[&str]| words.iter().map(|word| word.to_string()).collect::<HashSet<_>>();
+        assert_eq!(jaccard_similarity(&set(&["a", "b"]), &set(&["b", "c"])), 1.0 / 3.0);
+        assert_eq!(jaccard_similarity(&set(&[]), &set(&[])), 0.0);
+    }
+}
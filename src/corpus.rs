@@ -0,0 +1,152 @@
+use pdf_parser::Block;
+use pdf_parser::tokenize::tokenize;
+use glob::glob;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Debug)]
+pub struct WordFrequency {
+    pub word: String,
+    pub frequency: usize,
+    pub document_frequency: usize,
+}
+
+// Finds every `*_processed.json` file below `dir`
+pub fn find_processed_files(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let pattern = format!("{}/**/*_processed.json", dir.display());
+    let mut files = Vec::new();
+    for entry in glob(&pattern)? {
+        files.push(entry?);
+    }
+    files.sort();
+    Ok(files)
+}
+
+pub fn read_processed_blocks(path: &Path) -> Result<Vec<Block>, Box<dyn std::error::Error>> {
+    let content = fs::read_to_string(path)?;
+    match serde_json::from_str(&content) {
+        Ok(blocks) => Ok(blocks),
+        Err(e) => Err(format!("Invalid processed JSON in {:?}: {}", path, e).into()),
+    }
+}
+
+// Counts the words used in `SectionHeader` blocks across a set of documents.
+// Words appearing in fewer than `min_df` documents are dropped, and the result
+// is sorted by descending frequency and truncated to `top_k` if given.
+pub fn heading_word_frequencies(
+    documents: &[Vec<Block>],
+    top_k: Option<usize>,
+    min_df: usize,
+) -> Vec<WordFrequency> {
+    let mut frequencies: HashMap<String, usize> = HashMap::new();
+    let mut document_frequencies: HashMap<String, usize> = HashMap::new();
+
+    for blocks in documents {
+        let mut seen = HashSet::new();
+        for block in blocks.iter().filter(|block| block.block_type == "SectionHeader") {
+            for word in tokenize(&block.text) {
+                *frequencies.entry(word.clone()).or_insert(0) += 1;
+                seen.insert(word);
+            }
+        }
+        for word in seen {
+            *document_frequencies.entry(word).or_insert(0) += 1;
+        }
+    }
+
+    let mut words: Vec<WordFrequency> = frequencies
+        .into_iter()
+        .map(|(word, frequency)| {
+            let document_frequency = document_frequencies[&word];
+            WordFrequency {
+                word,
+                frequency,
+                document_frequency,
+            }
+        })
+        .filter(|entry| entry.document_frequency >= min_df)
+        .collect();
+
+    words.sort_by(|a, b| b.frequency.cmp(&a.frequency).then_with(|| a.word.cmp(&b.word)));
+    if let Some(top_k) = top_k {
+        words.truncate(top_k);
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heading(text: &str) -> Block {
+        Block {
+            block_type: "SectionHeader".to_string(),
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn paragraph(text: &str) -> Block {
+        Block {
+            block_type: "Text".to_string(),
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn counts(words: &[WordFrequency]) -> Vec<(&str, usize, usize)> {
+        words
+            .iter()
+            .map(|entry| (entry.word.as_str(), entry.frequency, entry.document_frequency))
+            .collect()
+    }
+
+    fn corpus() -> Vec<Vec<Block>> {
+        vec![
+            vec![heading("Introduction"), heading("Related Work"), paragraph("Introduction text")],
+            vec![heading("1. Introduction"), heading("Methods and Results")],
+            vec![heading("Results"), heading("Results of the second study")],
+        ]
+    }
+
+    #[test]
+    fn counts_heading_words_only() {
+        let words = heading_word_frequencies(&corpus(), None, 1);
+        assert_eq!(
+            counts(&words),
+            vec![
+                ("results", 3, 2),
+                ("introduction", 2, 2),
+                ("methods", 1, 1),
+                ("related", 1, 1),
+                ("second", 1, 1),
+                ("study", 1, 1),
+                ("work", 1, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn min_df_and_top_k_filter_words() {
+        let words = heading_word_frequencies(&corpus(), None, 2);
+        assert_eq!(counts(&words), vec![("results", 3, 2), ("introduction", 2, 2)]);
+
+        let words = heading_word_frequencies(&corpus(), Some(1), 1);
+        assert_eq!(counts(&words), vec![("results", 3, 2)]);
+    }
+
+    #[test]
+    fn finds_processed_files_recursively() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("sub")).unwrap();
+        for name in ["a_processed.json", "sub/b_processed.json", "a.json", "a_processed.md"] {
+            fs::write(dir.path().join(name), "[]").unwrap();
+        }
+
+        let files = find_processed_files(dir.path()).unwrap();
+        let names: Vec<&Path> = files.iter().map(|file| file.strip_prefix(dir.path()).unwrap()).collect();
+        assert_eq!(names, [Path::new("a_processed.json"), Path::new("sub/b_processed.json")]);
+    }
+}
@@ -0,0 +1,28 @@
+/// 64-bit FNV-1a hash, used to spot byte-identical input files in a batch run
+/// ```
+/// use pdf_parser::dedup::fnv1a_hash;
+///
+/// assert_eq!(fnv1a_hash(b""), 0xcbf29ce484222325);
+/// assert_eq!(fnv1a_hash(b"same"), fnv1a_hash(b"same"));
+/// assert_ne!(fnv1a_hash(b"same"), fnv1a_hash(b"Same"));
+/// ```
+pub fn fnv1a_hash(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_reference_fnv1a_values() {
+        assert_eq!(fnv1a_hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a_hash(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+}
@@ -0,0 +1,262 @@
+// Fallback decoding for Marker JSON files that were written in a legacy
+// single-byte encoding instead of UTF-8, and checks that processed text
+// survives conversion to one
+use crate::Block;
+use clap::ValueEnum;
+
+// Windows-1252 assigns printable characters to most of 0x80-0x9F, where
+// Latin-1 has C1 control codes. `None` marks the five unassigned bytes.
+const WINDOWS_1252_HIGH: [Option<char>; 32] = [
+    Some('€'), None, Some('‚'), Some('ƒ'), Some('„'), Some('…'), Some('†'), Some('‡'),
+    Some('ˆ'), Some('‰'), Some('Š'), Some('‹'), Some('Œ'), None, Some('Ž'), None,
+    None, Some('‘'), Some('’'), Some('“'), Some('”'), Some('•'), Some('–'), Some('—'),
+    Some('˜'), Some('™'), Some('š'), Some('›'), Some('œ'), None, Some('ž'), Some('Ÿ'),
+];
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    #[value(name = "latin1", alias = "iso-8859-1")]
+    Latin1,
+    #[value(name = "windows-1252", alias = "cp1252")]
+    Windows1252,
+}
+
+#[derive(Debug)]
+pub struct CharsetViolation {
+    pub id: String,
+    pub chars: Vec<char>,
+}
+
+impl Encoding {
+    /// Name of the encoding as used in messages
+    /// ```
+    /// use pdf_parser::encoding::Encoding;
+    ///
+    /// assert_eq!(Encoding::Windows1252.label(), "Windows-1252");
+    /// ```
+    pub fn label(self) -> &'static str {
+        match self {
+            Encoding::Latin1 => "ISO-8859-1",
+            Encoding::Windows1252 => "Windows-1252",
+        }
+    }
+}
+
+/// Guesses the single-byte encoding of non-UTF-8 input. Bytes in 0x80-0x9F are
+/// almost never C1 controls in real documents, so their presence points to
+/// Windows-1252 unless one of them is unassigned there.
+/// ```
+/// use pdf_parser::encoding::{Encoding, detect_single_byte_encoding};
+///
+/// // “quoted” in Windows-1252
+/// assert_eq!(detect_single_byte_encoding(b"\x93quoted\x94"), Encoding::Windows1252);
+/// assert_eq!(detect_single_byte_encoding(b"caf\xe9"), Encoding::Latin1);
+/// ```
+pub fn detect_single_byte_encoding(bytes: &[u8]) -> Encoding {
+    let high_control_bytes: Vec<u8> = bytes
+        .iter()
+        .copied()
+        .filter(|byte| (0x80..=0x9f).contains(byte))
+        .collect();
+    let all_assigned = high_control_bytes
+        .iter()
+        .all(|&byte| WINDOWS_1252_HIGH[(byte - 0x80) as usize].is_some());
+
+    if !high_control_bytes.is_empty() && all_assigned {
+        Encoding::Windows1252
+    } else {
+        Encoding::Latin1
+    }
+}
+
+/// Decodes single-byte encoded text
+/// ```
+/// use pdf_parser::encoding::{Encoding, decode};
+///
+/// assert_eq!(decode(b"\x80 caf\xe9", Encoding::Windows1252), "€ café");
+/// assert_eq!(decode(b"caf\xe9", Encoding::Latin1), "café");
+/// ```
+pub fn decode(bytes: &[u8], encoding: Encoding) -> String {
+    bytes.iter().map(|&byte| decode_byte(byte, encoding)).collect()
+}
+
+/// Decodes mostly-UTF-8 input, keeping every valid UTF-8 sequence and decoding
+/// only the invalid bytes in the single-byte encoding detected from them, so
+/// a file with one stray Windows-1252 byte keeps its multibyte characters
+/// ```
+/// use pdf_parser::encoding::{Encoding, decode_invalid_runs};
+///
+/// let mut bytes = "日本 ".as_bytes().to_vec();
+/// bytes.extend(b"\x93quoted\x94");
+/// assert_eq!(decode_invalid_runs(&bytes), ("日本 “quoted”".to_string(), Encoding::Windows1252));
+/// ```
+pub fn decode_invalid_runs(bytes: &[u8]) -> (String, Encoding) {
+    let invalid: Vec<u8> = bytes
+        .utf8_chunks()
+        .flat_map(|chunk| chunk.invalid().iter().copied())
+        .collect();
+    let encoding = detect_single_byte_encoding(&invalid);
+
+    let mut decoded = String::with_capacity(bytes.len());
+    for chunk in bytes.utf8_chunks() {
+        decoded.push_str(chunk.valid());
+        decoded.extend(chunk.invalid().iter().map(|&byte| decode_byte(byte, encoding)));
+    }
+    (decoded, encoding)
+}
+
+/// The byte `c` is written as in `encoding`, if it has one that decodes back
+/// to `c`
+/// ```
+/// use pdf_parser::encoding::{Encoding, encode_char};
+///
+/// assert_eq!(encode_char('€', Encoding::Windows1252), Some(0x80));
+/// assert_eq!(encode_char('€', Encoding::Latin1), None);
+/// ```
+pub fn encode_char(c: char, encoding: Encoding) -> Option<u8> {
+    let byte = match (encoding, c as u32) {
+        (_, code @ 0..=0xff) => code as u8,
+        (Encoding::Windows1252, _) => {
+            let index = WINDOWS_1252_HIGH.iter().position(|&high| high == Some(c))?;
+            0x80 + index as u8
+        }
+        (Encoding::Latin1, _) => return None,
+    };
+    (decode_byte(byte, encoding) == c).then_some(byte)
+}
+
+/// Every block whose text contains characters that cannot be written in
+/// `encoding`, with those characters listed once each in order of appearance
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::encoding::{Encoding, validate_charset};
+///
+/// let block = Block {
+///     id: "/page/0/Text/0".to_string(),
+///     text: "5 € – or ¥5".to_string(),
+///     ..Default::default()
+/// };
+/// let violations = validate_charset(&[block], Encoding::Latin1);
+/// assert_eq!(violations[0].id, "/page/0/Text/0");
+/// assert_eq!(violations[0].chars, ['€', '–']);
+/// ```
+pub fn validate_charset(blocks: &[Block], encoding: Encoding) -> Vec<CharsetViolation> {
+    blocks
+        .iter()
+        .filter_map(|block| {
+            let mut chars: Vec<char> = Vec::new();
+            for c in block.text.chars() {
+                if encode_char(c, encoding).is_none() && !chars.contains(&c) {
+                    chars.push(c);
+                }
+            }
+            (!chars.is_empty()).then(|| CharsetViolation {
+                id: block.id.clone(),
+                chars,
+            })
+        })
+        .collect()
+}
+
+fn decode_byte(byte: u8, encoding: Encoding) -> char {
+    match (encoding, byte) {
+        (Encoding::Windows1252, 0x80..=0x9f) => {
+            WINDOWS_1252_HIGH[(byte - 0x80) as usize].unwrap_or(byte as char)
+        }
+        // Latin-1 maps every byte to the code point of the same value
+        _ => byte as char,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_utf8_is_kept_next_to_windows_1252_bytes() {
+        // "naïve “quote” café" where only the quotes are Windows-1252 bytes
+        let mut bytes = "naïve ".as_bytes().to_vec();
+        bytes.extend([0x93, b'q', b'u', b'o', b't', b'e', 0x94]);
+        bytes.extend(" café".as_bytes());
+
+        let (decoded, encoding) = decode_invalid_runs(&bytes);
+        assert_eq!(encoding, Encoding::Windows1252);
+        assert_eq!(decoded, "naïve “quote” café");
+    }
+
+    #[test]
+    fn valid_utf8_is_kept_next_to_latin1_bytes() {
+        let mut bytes = "日本 ".as_bytes().to_vec();
+        bytes.extend([b'f', b'a', 0xE7, b'a', b'd', b'e']);
+
+        let (decoded, encoding) = decode_invalid_runs(&bytes);
+        assert_eq!(encoding, Encoding::Latin1);
+        assert_eq!(decoded, "日本 façade");
+    }
+
+    #[test]
+    fn truncated_multibyte_sequences_are_decoded_byte_by_byte() {
+        // 0xE2 0x80 starts a three-byte sequence that never finishes
+        let (decoded, _) = decode_invalid_runs(&[b'a', 0xE2, 0x80, b'b']);
+        assert_eq!(decoded, "aâ€b");
+    }
+
+    #[test]
+    fn whole_file_decoding_and_detection() {
+        assert_eq!(decode(&[0x80, 0xE9], Encoding::Windows1252), "€é");
+        assert_eq!(decode(&[0x80, 0xE9], Encoding::Latin1), "\u{80}é");
+        assert_eq!(detect_single_byte_encoding(&[0xE9]), Encoding::Latin1);
+        // 0x81 is unassigned in Windows-1252
+        assert_eq!(detect_single_byte_encoding(&[0x80, 0x81]), Encoding::Latin1);
+    }
+
+    fn block(id: &str, text: &str) -> Block {
+        Block {
+            id: id.to_string(),
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn latin1_and_windows_1252_encodable_characters() {
+        assert_eq!(encode_char('é', Encoding::Latin1), Some(0xE9));
+        assert_eq!(encode_char('ÿ', Encoding::Latin1), Some(0xFF));
+        assert_eq!(encode_char('€', Encoding::Latin1), None);
+        assert_eq!(encode_char('€', Encoding::Windows1252), Some(0x80));
+        assert_eq!(encode_char('—', Encoding::Windows1252), Some(0x97));
+        assert_eq!(encode_char('日', Encoding::Windows1252), None);
+        // U+0080 is a C1 control in Latin-1, but 0x80 is € in Windows-1252
+        assert_eq!(encode_char('\u{80}', Encoding::Latin1), Some(0x80));
+        assert_eq!(encode_char('\u{80}', Encoding::Windows1252), None);
+    }
+
+    #[test]
+    fn blocks_with_characters_outside_latin1_are_reported() {
+        let blocks = [
+            block("/page/0/Text/0", "Plain café text"),
+            block("/page/0/Text/1", "Costs 5 € — or “more” €"),
+            block("/page/1/Text/0", "日本語"),
+        ];
+
+        let violations = validate_charset(&blocks, Encoding::Latin1);
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].id, "/page/0/Text/1");
+        assert_eq!(violations[0].chars, ['€', '—', '“', '”']);
+        assert_eq!(violations[1].id, "/page/1/Text/0");
+        assert_eq!(violations[1].chars, ['日', '本', '語']);
+    }
+
+    #[test]
+    fn windows_1252_accepts_its_extra_punctuation() {
+        let blocks = [
+            block("/page/0/Text/0", "Costs 5 € — or “more”"),
+            block("/page/0/Text/1", "Emoji 🙂 and Ω"),
+        ];
+
+        let violations = validate_charset(&blocks, Encoding::Windows1252);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].id, "/page/0/Text/1");
+        assert_eq!(violations[0].chars, ['🙂', 'Ω']);
+    }
+}
@@ -0,0 +1,32 @@
+// Errors from writing the tool's output files
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum PdfParserError {
+    // The temporary file next to `path` could not be created, written or synced
+    Write { path: PathBuf, source: std::io::Error },
+    // The finished temporary file could not be moved over `path`
+    Rename { path: PathBuf, source: std::io::Error },
+}
+
+impl fmt::Display for PdfParserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PdfParserError::Write { path, source } => {
+                write!(f, "could not write {}: {}", path.display(), source)
+            }
+            PdfParserError::Rename { path, source } => {
+                write!(f, "could not replace {}: {}", path.display(), source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PdfParserError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PdfParserError::Write { source, .. } | PdfParserError::Rename { source, .. } => Some(source),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::Block;
+use sha2::{Digest, Sha256};
+
+/// Canonical fingerprint of a document's content: SHA-256 over the
+/// concatenated `block_type + text` of every block, taken in id order so that
+/// neither block order in the input nor any other field (html, geometry, file
+/// timestamps) affects the result. Returned as lowercase hex.
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::fingerprint::document_content_hash;
+///
+/// let block = |id: &str, text: &str, html: &str| Block {
+///     id: id.to_string(),
+///     block_type: "Text".to_string(),
+///     text: text.to_string(),
+///     html: html.to_string(),
+///     ..Default::default()
+/// };
+/// let a = [block("/page/0/Text/0", "One", "<p>One</p>"), block("/page/0/Text/1", "Two", "")];
+/// let b = [block("/page/0/Text/1", "Two", "<p>Two</p>"), block("/page/0/Text/0", "One", "")];
+/// assert_eq!(document_content_hash(&a), document_content_hash(&b));
+/// assert_eq!(document_content_hash(&a).len(), 64);
+/// ```
+pub fn document_content_hash(blocks: &[Block]) -> String {
+    let mut sorted: Vec<&Block> = blocks.iter().collect();
+    sorted.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let mut hasher = Sha256::new();
+    for block in sorted {
+        hasher.update(block.block_type.as_bytes());
+        hasher.update(block.text.as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(id: &str, block_type: &str, text: &str) -> Block {
+        Block {
+            id: id.to_string(),
+            block_type: block_type.to_string(),
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    // A single block whose type and text concatenate to `content`
+    fn hash_of(content: &str) -> String {
+        let (block_type, text) = content.split_at(content.len() / 2);
+        document_content_hash(&[block("/page/0/Text/0", block_type, text)])
+    }
+
+    // FIPS 180-2 / NIST CAVS example vectors
+    #[test]
+    fn matches_sha256_test_vectors() {
+        assert_eq!(hash_of(""), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_eq!(hash_of("abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(
+            hash_of("abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+        assert_eq!(
+            hash_of(
+                "abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu"
+            ),
+            "cf5b16a778af8380036ce59e7b0492370b249b11e8f07a51afac45037afee9d1"
+        );
+        assert_eq!(document_content_hash(&[]), hash_of(""));
+    }
+
+    #[test]
+    fn hash_is_the_concatenation_of_type_and_text_in_id_order() {
+        let blocks = [
+            block("/page/0/Text/2", "Text", "world"),
+            block("/page/0/SectionHeader/1", "SectionHeader", "Hello"),
+        ];
+        let expected = format!("{:x}", Sha256::digest("SectionHeaderHelloTextworld"));
+        assert_eq!(document_content_hash(&blocks), expected);
+    }
+
+    #[test]
+    fn hash_ignores_block_order_and_other_fields() {
+        let a = block("/page/0/Text/1", "Text", "First");
+        let b = block("/page/0/Text/2", "Text", "Second");
+        let mut b_with_html = b.clone();
+        b_with_html.html = "<p>Second</p>".to_string();
+        b_with_html.bbox = Some(vec![0.0, 0.0, 10.0, 10.0]);
+
+        let expected = document_content_hash(&[a.clone(), b]);
+        assert_eq!(document_content_hash(&[b_with_html, a]), expected);
+    }
+
+    #[test]
+    fn hash_changes_with_content() {
+        let original = document_content_hash(&[block("/page/0/Text/1", "Text", "The fee is 10")]);
+        let edited = document_content_hash(&[block("/page/0/Text/1", "Text", "The fee is 11")]);
+        let retyped = document_content_hash(&[block("/page/0/Text/1", "Caption", "The fee is 10")]);
+        assert_ne!(original, edited);
+        assert_ne!(original, retyped);
+    }
+}
@@ -0,0 +1,222 @@
+use crate::{Block, extract_text_from_html, page_from_id, truncate_text};
+use regex::Regex;
+use std::collections::HashSet;
+
+struct Footnote {
+    number: u32,
+    page: Option<u32>,
+    id: String,
+    text: String,
+}
+
+/// Replaces `[N]` footnote markers (and Marker's `<sup>N</sup>` markers) in
+/// the text of each block with `(footnote: <text>)`, truncating the footnote
+/// text to `max_length` characters. Footnote numbering often restarts on each
+/// page, so a marker is paired with the footnote of that number on the same
+/// page, or failing that the nearest one on a following page; footnotes on
+/// earlier pages are never used. Markers are one to three digits, so years
+/// in brackets are left alone. Footnotes that were inlined are removed from
+/// the output.
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::footnotes::inline_footnotes;
+///
+/// let block = |id: &str, block_type: &str, text: &str| Block {
+///     id: id.to_string(),
+///     block_type: block_type.to_string(),
+///     text: text.to_string(),
+///     ..Default::default()
+/// };
+/// let blocks = vec![
+///     block("/page/0/Text/0", "Text", "Founded in [1990] as noted[1]."),
+///     block("/page/0/Footnote/1", "Footnote", "1. See the annual report."),
+/// ];
+/// let inlined = inline_footnotes(blocks, 100);
+/// assert_eq!(inlined.len(), 1);
+/// assert_eq!(inlined[0].text, "Founded in [1990] as noted (footnote: See the annual report.).");
+/// ```
+pub fn inline_footnotes(blocks: Vec<Block>, max_length: usize) -> Vec<Block> {
+    let footnotes: Vec<Footnote> = blocks
+        .iter()
+        .filter(|block| block.block_type == "Footnote" || block.block_type == "Citation")
+        .filter_map(parse_footnote)
+        .collect();
+    if footnotes.is_empty() {
+        return blocks;
+    }
+
+    let sup_re = Regex::new(r"(?i)<sup>\s*(\d{1,3})\s*</sup>").unwrap();
+    let marker_re = Regex::new(r"\s*\[(\d{1,3})\]").unwrap();
+    let mut inlined: HashSet<String> = HashSet::new();
+
+    let mut result: Vec<Block> = blocks
+        .into_iter()
+        .map(|mut block| {
+            if block.block_type == "Footnote" || block.block_type == "Citation" {
+                return block;
+            }
+
+            // Turn superscript markers into [N] so both styles are handled alike
+            let html = sup_re.replace_all(&block.html, " [$1]");
+            let text = if html != block.html {
+                extract_text_from_html(&html)
+            } else {
+                block.text.clone()
+            };
+
+            let page = page_from_id(&block.id);
+            let replaced = marker_re.replace_all(&text, |caps: &regex::Captures| {
+                let number: u32 = caps[1].parse().unwrap_or(0);
+                match find_footnote(&footnotes, number, page) {
+                    Some(footnote) => {
+                        inlined.insert(footnote.id.clone());
+                        format!(" (footnote: {})", truncate_text(&footnote.text, max_length))
+                    }
+                    None => caps[0].to_string(),
+                }
+            });
+            block.text = replaced.into_owned();
+            block
+        })
+        .collect();
+
+    result.retain(|block| !inlined.contains(&block.id));
+    result
+}
+
+// Reads the footnote number from a leading <sup>N</sup> in the HTML, or a
+// leading `N`, `N.` or `[N]` in the text. Numbers have at most three digits,
+// so a definition starting with a year is not taken for a marker.
+fn parse_footnote(block: &Block) -> Option<Footnote> {
+    let sup_re = Regex::new(r"(?is)^\s*(?:<p[^>]*>)?\s*<sup>\s*(\d{1,3})\s*</sup>").unwrap();
+    let text_re = Regex::new(r"^\s*\[?(\d{1,3})[\].]?\s+").unwrap();
+
+    let number: u32 = match sup_re.captures(&block.html) {
+        Some(caps) => caps[1].parse().ok()?,
+        None => text_re.captures(&block.text)?[1].parse().ok()?,
+    };
+    let text = text_re.replace(&block.text, "").into_owned();
+
+    Some(Footnote {
+        number,
+        page: page_from_id(&block.id),
+        id: block.id.clone(),
+        text,
+    })
+}
+
+// The footnote numbered `number` on `page`, or else on the nearest page
+// after it
+fn find_footnote(footnotes: &[Footnote], number: u32, page: Option<u32>) -> Option<&Footnote> {
+    footnotes
+        .iter()
+        .filter(|footnote| footnote.number == number && footnote.page >= page)
+        .min_by_key(|footnote| footnote.page)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(id: &str, block_type: &str, html: &str, text: &str) -> Block {
+        Block {
+            id: id.to_string(),
+            block_type: block_type.to_string(),
+            html: html.to_string(),
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn text_block(id: &str, text: &str) -> Block {
+        block(id, "Text", &format!("<p>{}</p>", text), text)
+    }
+
+    fn footnote(id: &str, text: &str) -> Block {
+        block(id, "Footnote", &format!("<p>{}</p>", text), text)
+    }
+
+    fn texts(blocks: &[Block]) -> Vec<&str> {
+        blocks.iter().map(|block| block.text.as_str()).collect()
+    }
+
+    #[test]
+    fn markers_use_the_footnote_on_the_same_page() {
+        let blocks = vec![
+            text_block("/page/0/Text/0", "First claim [1]."),
+            footnote("/page/0/Footnote/1", "1 Page zero note."),
+            text_block("/page/1/Text/0", "Second claim [1]."),
+            footnote("/page/1/Footnote/1", "1 Page one note."),
+        ];
+        let result = inline_footnotes(blocks, 100);
+        assert_eq!(
+            texts(&result),
+            [
+                "First claim (footnote: Page zero note.).",
+                "Second claim (footnote: Page one note.).",
+            ]
+        );
+    }
+
+    #[test]
+    fn markers_fall_forward_to_the_nearest_following_page_only() {
+        let blocks = vec![
+            footnote("/page/0/Footnote/0", "2 Earlier note."),
+            text_block("/page/1/Text/0", "See [2] and [3]."),
+            footnote("/page/3/Footnote/0", "3 Later note."),
+            footnote("/page/2/Footnote/0", "3 Next page note."),
+        ];
+        let result = inline_footnotes(blocks, 100);
+        assert_eq!(
+            texts(&result),
+            [
+                "2 Earlier note.",
+                "See [2] and (footnote: Next page note.).",
+                "3 Later note.",
+            ]
+        );
+    }
+
+    #[test]
+    fn years_are_not_markers_or_footnote_numbers() {
+        let blocks = vec![
+            text_block("/page/0/Text/0", "As shown in [1998] and [4]."),
+            footnote("/page/0/Footnote/1", "1998 Smith, A history of notes."),
+            footnote("/page/0/Footnote/2", "4. A real note."),
+        ];
+        let result = inline_footnotes(blocks, 100);
+        assert_eq!(
+            texts(&result),
+            [
+                "As shown in [1998] and (footnote: A real note.).",
+                "1998 Smith, A history of notes.",
+            ]
+        );
+    }
+
+    #[test]
+    fn superscript_markers_and_truncation() {
+        let blocks = vec![
+            block(
+                "/page/0/Text/0",
+                "Text",
+                "<p>Claim<sup>7</sup> here</p>",
+                "Claim7 here",
+            ),
+            block(
+                "/page/0/Footnote/1",
+                "Footnote",
+                "<p><sup>7</sup> A rather long explanation of the claim</p>",
+                "7 A rather long explanation of the claim",
+            ),
+        ];
+        let result = inline_footnotes(blocks, 14);
+        assert_eq!(texts(&result), ["Claim (footnote: A rather long…) here"]);
+    }
+
+    #[test]
+    fn text_without_footnotes_is_unchanged() {
+        let blocks = vec![text_block("/page/0/Text/0", "Array index [0] stays.")];
+        assert_eq!(texts(&inline_footnotes(blocks, 100)), ["Array index [0] stays."]);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::Block;
+use serde::Serialize;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// The front matter fields, in the order they are written
+#[derive(Serialize)]
+struct FrontMatter<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<&'a str>,
+    date: String,
+    source_file: String,
+}
+
+/// Builds the YAML front matter block used by static site generators. Marker
+/// JSON carries no document metadata, so the date is taken from the input
+/// file's modification time.
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::front_matter::render_front_matter;
+/// use std::path::Path;
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let header = Block {
+///     block_type: "SectionHeader".to_string(),
+///     text: "Chapter 1".to_string(),
+///     ..Default::default()
+/// };
+/// let modified = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+/// assert_eq!(
+///     render_front_matter(&[header], Path::new("novel.json"), modified)?,
+///     "---\ntitle: Chapter 1\ndate: 2023-11-14\nsource_file: novel.json\n---\n\n"
+/// );
+/// # Ok::<(), serde_yaml::Error>(())
+/// ```
+pub fn render_front_matter(
+    blocks: &[Block],
+    input_path: &Path,
+    modified: SystemTime,
+) -> Result<String, serde_yaml::Error> {
+    let front_matter = FrontMatter {
+        title: blocks
+            .iter()
+            .find(|block| block.block_type == "SectionHeader")
+            .map(|header| header.text.as_str()),
+        date: format_date(modified),
+        source_file: input_path.to_string_lossy().into_owned(),
+    };
+    Ok(format!("---\n{}---\n\n", serde_yaml::to_string(&front_matter)?))
+}
+
+// Formats a timestamp as a YYYY-MM-DD date (UTC)
+fn format_date(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// Converts days since 1970-01-01 into a (year, month, day) Gregorian date,
+// following Howard Hinnant's `civil_from_days` algorithm
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn block(block_type: &str, text: &str) -> Block {
+        Block {
+            block_type: block_type.to_string(),
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    // Parses the YAML between the two `---` lines
+    fn parse(front_matter: &str) -> serde_yaml::Mapping {
+        let body = front_matter
+            .strip_prefix("---\n")
+            .and_then(|rest| rest.strip_suffix("---\n\n"))
+            .expect("front matter is fenced by --- lines");
+        serde_yaml::from_str(body).expect("front matter is valid YAML")
+    }
+
+    fn field<'a>(mapping: &'a serde_yaml::Mapping, key: &str) -> Option<&'a str> {
+        mapping.get(key).and_then(|value| value.as_str())
+    }
+
+    #[test]
+    fn title_comes_from_the_first_section_header() {
+        let blocks = [
+            block("Text", "Preface"),
+            block("SectionHeader", "Chapter 1"),
+            block("SectionHeader", "Chapter 2"),
+        ];
+        let modified = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let yaml = parse(&render_front_matter(&blocks, Path::new("books/novel.json"), modified).unwrap());
+
+        assert_eq!(field(&yaml, "title"), Some("Chapter 1"));
+        assert_eq!(field(&yaml, "date"), Some("2023-11-14"));
+        assert_eq!(field(&yaml, "source_file"), Some("books/novel.json"));
+    }
+
+    #[test]
+    fn special_characters_stay_valid_yaml() {
+        let title = "Part: \"One\" #1 - [draft] {x} & 'more'\nsecond line ñ";
+        let blocks = [block("SectionHeader", title)];
+        let yaml = parse(&render_front_matter(&blocks, Path::new("a: b.json"), UNIX_EPOCH).unwrap());
+
+        assert_eq!(field(&yaml, "title"), Some(title));
+        assert_eq!(field(&yaml, "source_file"), Some("a: b.json"));
+    }
+
+    #[test]
+    fn title_is_omitted_without_headers() {
+        let yaml = parse(&render_front_matter(&[block("Text", "Body")], Path::new("a.json"), UNIX_EPOCH).unwrap());
+        assert_eq!(field(&yaml, "title"), None);
+        assert_eq!(field(&yaml, "date"), Some("1970-01-01"));
+    }
+
+    #[test]
+    fn dates_across_leap_years_and_centuries() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(47_541), (2100, 3, 1));
+    }
+}
@@ -0,0 +1,102 @@
+use crate::Block;
+use regex::Regex;
+
+/// Reads the heading level from the first <h1>..<h6> tag in the block HTML
+/// ```
+/// use pdf_parser::headings::heading_level;
+///
+/// assert_eq!(heading_level("<h3 id=\"intro\">Introduction</h3>"), Some(3));
+/// assert_eq!(heading_level("<p>Body</p>"), None);
+/// ```
+pub fn heading_level(html: &str) -> Option<usize> {
+    let re = Regex::new(r"(?i)<h([1-6])[\s>]").unwrap();
+    re.captures(html).and_then(|caps| caps[1].parse().ok())
+}
+
+/// Rewrites <hN> tags deeper than `max_level` to <h{max_level}> so that no
+/// heading exceeds the depth a downstream renderer supports
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::headings::cap_heading_levels;
+///
+/// let header = Block { html: "<h5>Detail</h5>".to_string(), ..Default::default() };
+/// assert_eq!(cap_heading_levels(vec![header], 3)[0].html, "<h3>Detail</h3>");
+/// ```
+pub fn cap_heading_levels(blocks: Vec<Block>, max_level: u8) -> Vec<Block> {
+    let max_level = max_level.clamp(1, 6);
+    let tag_re = Regex::new(r"(?i)<(/?)h([1-6])([\s>])").unwrap();
+
+    blocks
+        .into_iter()
+        .map(|mut block| {
+            block.html = tag_re
+                .replace_all(&block.html, |caps: &regex::Captures| {
+                    let level: u8 = caps[2].parse().unwrap_or(1);
+                    format!("<{}h{}{}", &caps[1], level.min(max_level), &caps[3])
+                })
+                .into_owned();
+            block
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(htmls: &[&str]) -> Vec<Block> {
+        htmls
+            .iter()
+            .map(|html| Block {
+                block_type: "SectionHeader".to_string(),
+                html: html.to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn htmls(blocks: &[Block]) -> Vec<&str> {
+        blocks.iter().map(|block| block.html.as_str()).collect()
+    }
+
+    #[test]
+    fn three_levels_capped_at_two() {
+        let blocks = headers(&["<h1>Book</h1>", "<h2>Part</h2>", "<h3 id=\"c\">Chapter</h3>"]);
+        assert_eq!(
+            htmls(&cap_heading_levels(blocks, 2)),
+            ["<h1>Book</h1>", "<h2>Part</h2>", "<h2 id=\"c\">Chapter</h2>"]
+        );
+    }
+
+    #[test]
+    fn five_levels_capped_at_three() {
+        let blocks = headers(&[
+            "<h1>A</h1>",
+            "<h2>B</h2>",
+            "<h3>C</h3>",
+            "<H4>D</H4>",
+            "<h5>E</h5>",
+        ]);
+        let capped = cap_heading_levels(blocks, 3);
+        assert_eq!(
+            htmls(&capped),
+            ["<h1>A</h1>", "<h2>B</h2>", "<h3>C</h3>", "<h3>D</h3>", "<h3>E</h3>"]
+        );
+        let levels: Vec<Option<usize>> = capped.iter().map(|block| heading_level(&block.html)).collect();
+        assert_eq!(levels, [Some(1), Some(2), Some(3), Some(3), Some(3)]);
+    }
+
+    #[test]
+    fn deeper_tags_after_a_shallow_first_tag_are_capped() {
+        let blocks = headers(&["<h2>Title</h2><h5>Subtitle</h5>"]);
+        assert_eq!(htmls(&cap_heading_levels(blocks, 3)), ["<h2>Title</h2><h3>Subtitle</h3>"]);
+    }
+
+    #[test]
+    fn out_of_range_maximums_are_clamped() {
+        assert_eq!(htmls(&cap_heading_levels(headers(&["<h3>X</h3>"]), 0)), ["<h1>X</h1>"]);
+        assert_eq!(htmls(&cap_heading_levels(headers(&["<h6>X</h6>"]), 9)), ["<h6>X</h6>"]);
+        assert_eq!(heading_level("<p>No heading</p>"), None);
+        assert_eq!(heading_level("<header>x</header>"), None);
+    }
+}
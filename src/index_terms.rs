@@ -0,0 +1,165 @@
+use crate::{Block, extract_text_from_html};
+use regex::Regex;
+use serde::Serialize;
+
+// Back-of-book indexes are only looked for in this trailing fraction of blocks
+const INDEX_REGION_FRACTION: f64 = 0.05;
+
+// Ranges longer than this are more likely OCR errors than real page spans
+const MAX_RANGE_LENGTH: u32 = 200;
+
+#[derive(Serialize, Debug)]
+pub struct IndexEntry {
+    pub term: String,
+    pub pages: Vec<u32>,
+}
+
+/// Parses index entries of the form `term, 42, 67–70` from the last 5% of the
+/// document's blocks. Page ranges are expanded, including abbreviated ones
+/// such as `142–45`.
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::index_terms::extract_index_entries;
+///
+/// let text = |text: &str| Block { block_type: "Text".to_string(), text: text.to_string(), ..Default::default() };
+/// let mut blocks = vec![text("Body text."); 19];
+/// blocks.push(text("Apples, 12, 142–45"));
+///
+/// let entries = extract_index_entries(&blocks);
+/// assert_eq!(entries[0].term, "Apples");
+/// assert_eq!(entries[0].pages, [12, 142, 143, 144, 145]);
+/// ```
+pub fn extract_index_entries(blocks: &[Block]) -> Vec<IndexEntry> {
+    let region = ((blocks.len() as f64) * INDEX_REGION_FRACTION).ceil() as usize;
+    let line_break_re = Regex::new(r"(?i)<br\s*/?>|</p>|</li>|</div>").unwrap();
+    let entry_re = Regex::new(
+        r"^(?P<term>[^\d,].*?)\s*,\s*(?P<pages>\d+(?:\s*[-–—]\s*\d+)?(?:\s*,\s*\d+(?:\s*[-–—]\s*\d+)?)*)\s*\.?$",
+    )
+    .unwrap();
+
+    let mut entries = Vec::new();
+    for block in &blocks[blocks.len() - region..] {
+        // Index blocks often hold several entries separated by line breaks
+        let lines: Vec<String> = if block.html.is_empty() {
+            vec![block.text.clone()]
+        } else {
+            line_break_re
+                .split(&block.html)
+                .map(extract_text_from_html)
+                .collect()
+        };
+
+        for line in lines {
+            if let Some(caps) = entry_re.captures(&line) {
+                let pages = parse_pages(&caps["pages"]);
+                if !pages.is_empty() {
+                    entries.push(IndexEntry {
+                        term: caps["term"].trim().to_string(),
+                        pages,
+                    });
+                }
+            }
+        }
+    }
+    entries
+}
+
+// Expands `42, 67–70` into [42, 67, 68, 69, 70]
+fn parse_pages(pages: &str) -> Vec<u32> {
+    let mut result = Vec::new();
+    for part in pages.split(',') {
+        let bounds: Vec<&str> = part.split(['-', '–', '—']).map(str::trim).collect();
+        match bounds.as_slice() {
+            [page] => result.extend(page.parse::<u32>().ok()),
+            [start, end] => {
+                let (Ok(first), Some(last)) = (start.parse::<u32>(), expand_range_end(start, end))
+                else {
+                    continue;
+                };
+                if last >= first && last - first <= MAX_RANGE_LENGTH {
+                    result.extend(first..=last);
+                }
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+// Abbreviated range ends borrow their leading digits from the start, so
+// `142–45` ends at 145
+fn expand_range_end(start: &str, end: &str) -> Option<u32> {
+    if end.len() < start.len() {
+        format!("{}{}", &start[..start.len() - end.len()], end).parse().ok()
+    } else {
+        end.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(html: &str, text: &str) -> Block {
+        Block {
+            block_type: "Text".to_string(),
+            html: html.to_string(),
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    // 40 body blocks followed by `index`, so the index falls in the trailing 5%
+    fn document(index: Vec<Block>) -> Vec<Block> {
+        let mut blocks: Vec<Block> = (0..40).map(|_| block("<p>Body text.</p>", "Body text.")).collect();
+        blocks.extend(index);
+        blocks
+    }
+
+    fn entries(blocks: &[Block]) -> Vec<(String, Vec<u32>)> {
+        extract_index_entries(blocks)
+            .into_iter()
+            .map(|entry| (entry.term, entry.pages))
+            .collect()
+    }
+
+    #[test]
+    fn entries_are_read_from_line_broken_index_blocks() {
+        let blocks = document(vec![block(
+            "<p>Aquifers, 12, 40–42<br>Basalt, 7.</p><p>Chemistry of soils, 101-3</p>",
+            "",
+        )]);
+        assert_eq!(
+            entries(&blocks),
+            [
+                ("Aquifers".to_string(), vec![12, 40, 41, 42]),
+                ("Basalt".to_string(), vec![7]),
+                ("Chemistry of soils".to_string(), vec![101, 102, 103]),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_the_trailing_region_is_searched() {
+        // 42 blocks in all, of which the last 3 are searched
+        let mut blocks = document(vec![block("", "Deltas, 5"), block("", "Estuaries, 9")]);
+        blocks[0] = block("", "Early mention, 3");
+        blocks[39] = block("", "Floodplains, 11");
+        let terms: Vec<String> = entries(&blocks).into_iter().map(|(term, _)| term).collect();
+        assert_eq!(terms, ["Floodplains", "Deltas", "Estuaries"]);
+    }
+
+    #[test]
+    fn abbreviated_and_implausible_ranges() {
+        assert_eq!(parse_pages("142–45"), (142..=145).collect::<Vec<_>>());
+        assert_eq!(parse_pages("1998—2003"), (1998..=2003).collect::<Vec<_>>());
+        assert_eq!(parse_pages("10-1000"), Vec::<u32>::new());
+        assert_eq!(parse_pages("50-40, 3"), [3]);
+    }
+
+    #[test]
+    fn lines_that_are_not_entries_are_skipped() {
+        let blocks = [block("<p>Index</p><p>See also rivers</p><p>1999, 4</p>", "")];
+        assert!(entries(&blocks).is_empty());
+    }
+}
@@ -0,0 +1,212 @@
+// Output files that are never left half written
+use crate::error::PdfParserError;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Writes `contents` to `path` without ever leaving a partially written file
+/// behind: the data goes to a temporary file in the same directory, which is
+/// flushed to disk and then renamed over the destination. Rename is atomic
+/// within a filesystem, so readers see either the old file or the new one.
+/// ```
+/// use pdf_parser::io::atomic_write;
+///
+/// let dir = tempfile::tempdir()?;
+/// let path = dir.path().join("doc_processed.txt");
+/// atomic_write(&path, b"Fish & chips\n")?;
+/// assert_eq!(std::fs::read(&path)?, b"Fish & chips\n");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn atomic_write(path: &Path, contents: &[u8]) -> Result<(), PdfParserError> {
+    let mut file = AtomicFile::create(path)?;
+    file.write_all(contents).map_err(|source| PdfParserError::Write {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    file.commit()
+}
+
+/// A file written piece by piece with the same guarantee as atomic_write:
+/// `path` is only replaced by commit. Dropping an uncommitted file removes
+/// its temporary file and leaves `path` as it was.
+/// ```
+/// use pdf_parser::io::AtomicFile;
+/// use std::io::Write;
+///
+/// let dir = tempfile::tempdir()?;
+/// let path = dir.path().join("doc_processed.txt");
+/// let mut file = AtomicFile::create(&path)?;
+/// writeln!(file, "First")?;
+/// assert!(!path.exists());
+/// file.commit()?;
+/// assert_eq!(std::fs::read_to_string(&path)?, "First\n");
+///
+/// let mut file = AtomicFile::create(&path)?;
+/// writeln!(file, "Abandoned")?;
+/// drop(file);
+/// assert_eq!(std::fs::read_to_string(&path)?, "First\n");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct AtomicFile {
+    path: PathBuf,
+    temp_path: PathBuf,
+    // Only None once commit or drop has closed it
+    file: Option<BufWriter<File>>,
+}
+
+impl AtomicFile {
+    pub fn create(path: &Path) -> Result<Self, PdfParserError> {
+        let temp_path = temp_path_for(path);
+        let file = File::create(&temp_path).map_err(|source| PdfParserError::Write {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Ok(AtomicFile {
+            path: path.to_path_buf(),
+            temp_path,
+            file: Some(BufWriter::new(file)),
+        })
+    }
+
+    // The destination, not the temporary file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Flushes the data to disk and renames it over `path`
+    pub fn commit(mut self) -> Result<(), PdfParserError> {
+        let result = self
+            .file
+            .take()
+            .expect("file is open until committed")
+            .into_inner()
+            .map_err(|e| e.into_error())
+            .and_then(|file| file.sync_all())
+            .map_err(|source| PdfParserError::Write {
+                path: self.path.clone(),
+                source,
+            })
+            .and_then(|()| {
+                fs::rename(&self.temp_path, &self.path).map_err(|source| PdfParserError::Rename {
+                    path: self.path.clone(),
+                    source,
+                })
+            });
+
+        if result.is_ok() {
+            // Nothing left for drop to clean up
+            self.temp_path = PathBuf::new();
+        }
+        result
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.as_mut().expect("file is open until committed").write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.as_mut().expect("file is open until committed").flush()
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        // Closed first, as open files can't be removed on every platform.
+        // Best effort: the error that abandoned the file is what matters.
+        self.file = None;
+        if !self.temp_path.as_os_str().is_empty() {
+            let _ = fs::remove_file(&self.temp_path);
+        }
+    }
+}
+
+// Hidden sibling of `path`, unique per process so concurrent runs writing
+// into the same directory don't clobber each other's temp files
+fn temp_path_for(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "output".to_string());
+    path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn replaces_an_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.json");
+        fs::write(&path, "old contents that are longer").unwrap();
+
+        atomic_write(&path, b"new").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+        assert_eq!(entries(dir.path()), ["out.json"]);
+    }
+
+    #[test]
+    fn failed_rename_leaves_the_destination_and_no_temp_file() {
+        // Renaming a file over a non-empty directory fails on every platform
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.json");
+        fs::create_dir(&path).unwrap();
+        fs::write(path.join("keep.txt"), "kept").unwrap();
+
+        let error = atomic_write(&path, b"new").unwrap_err();
+        assert!(matches!(error, PdfParserError::Rename { .. }), "{:?}", error);
+        assert!(error.to_string().contains("out.json"), "{}", error);
+        assert_eq!(fs::read_to_string(path.join("keep.txt")).unwrap(), "kept");
+        assert_eq!(entries(dir.path()), ["out.json"]);
+    }
+
+    #[test]
+    fn failed_write_is_reported_with_the_destination() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing").join("out.json");
+
+        let error = atomic_write(&path, b"new").unwrap_err();
+        assert!(matches!(error, PdfParserError::Write { .. }), "{:?}", error);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn atomic_file_replaces_the_destination_only_on_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.json");
+        fs::write(&path, "old").unwrap();
+
+        let mut file = AtomicFile::create(&path).unwrap();
+        file.write_all(b"new ").unwrap();
+        file.write_all(b"contents").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old");
+
+        file.commit().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new contents");
+        assert_eq!(entries(dir.path()), ["out.json"]);
+    }
+
+    #[test]
+    fn abandoned_atomic_file_leaves_the_destination_and_no_temp_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.json");
+        fs::write(&path, "old").unwrap();
+
+        let mut file = AtomicFile::create(&path).unwrap();
+        file.write_all(b"partial").unwrap();
+        drop(file);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old");
+        assert_eq!(entries(dir.path()), ["out.json"]);
+    }
+}
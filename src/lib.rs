@@ -0,0 +1,830 @@
+// Flattening and post-processing of Marker JSON output. The
+// flatten_marker_output binary is a command-line wrapper around this crate.
+use clap::ValueEnum;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+pub mod abbreviations;
+pub mod base64;
+pub mod blockquote;
+pub mod boundaries;
+pub mod dedup;
+pub mod encoding;
+pub mod error;
+pub mod fingerprint;
+pub mod footnotes;
+pub mod front_matter;
+pub mod headings;
+pub mod index_terms;
+pub mod io;
+pub mod lists;
+pub mod markdown;
+pub mod ngrams;
+pub mod page_summary;
+pub mod parquet_format;
+pub mod punctuation;
+pub mod reader;
+pub mod sanitize;
+pub mod sections;
+pub mod stats;
+pub mod streaming;
+pub mod summarize;
+pub mod table_merge;
+pub mod tables;
+pub mod tokenize;
+pub mod toml_format;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Block {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub block_type: String,
+    #[serde(default)]
+    pub html: String,
+    #[serde(default)]
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub polygon: Option<Vec<Vec<f64>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bbox: Option<Vec<f64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<Block>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section_hierarchy: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub images: Option<serde_json::Value>,
+    // Set by --extract-tables to the file name of the CSV the table was
+    // written to, which sits next to the processed output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub table_csv_path: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Document {
+    pub children: Vec<Block>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    Json,
+    Text,
+    Markdown,
+    Toml,
+    Parquet,
+}
+
+impl OutputFormat {
+    /// File extension of the processed output in this format
+    /// ```
+    /// use pdf_parser::OutputFormat;
+    ///
+    /// assert_eq!(OutputFormat::Markdown.extension(), "md");
+    /// assert_eq!(OutputFormat::Text.extension(), "txt");
+    /// ```
+    pub fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Json => "json",
+            OutputFormat::Text => "txt",
+            OutputFormat::Markdown => "md",
+            OutputFormat::Toml => "toml",
+            OutputFormat::Parquet => "parquet",
+        }
+    }
+}
+
+// Options that control how each input file is processed and written
+#[derive(Debug, Clone)]
+pub struct ProcessingConfig {
+    pub marker_bin: String,
+    pub marker_batch_bin: String,
+    pub keep_intermediate: bool,
+    pub output_formats: Vec<OutputFormat>,
+    pub yaml_front_matter: bool,
+    pub auto_table_format: bool,
+    pub content_hash: bool,
+    pub group_by_section: bool,
+    pub extract_index: bool,
+    pub generate_report: bool,
+    pub stats: bool,
+    pub extract_tables: bool,
+    pub sliding_window_summary: Option<summarize::SlidingWindow>,
+    pub strict_utf8: bool,
+    pub preview: Option<usize>,
+    pub preview_raw: bool,
+    pub dedup: bool,
+    pub flatten_lists: bool,
+    pub detect_blockquotes: bool,
+    pub detect_doc_boundaries: bool,
+    pub split_at_boundaries: bool,
+    pub merge_split_tables: bool,
+    pub max_heading_depth: Option<u8>,
+    pub inline_footnotes: bool,
+    pub max_inline_footnote_length: usize,
+    pub expand_abbreviations: bool,
+    pub flag_punctuation_noise: bool,
+    pub drop_punctuation_noise: bool,
+    pub punctuation_threshold: f64,
+    pub strip_control_chars: bool,
+    pub strip_non_bmp: bool,
+    pub base64_encode: bool,
+    pub base64_decode: bool,
+    pub base64_fields: Vec<base64::Base64Field>,
+    pub validate_charset: Option<encoding::Encoding>,
+    pub filter: FilterConfig,
+}
+
+// Block types dropped during flattening unless --exclude-types replaces the list
+pub const DEFAULT_EXCLUDED_TYPES: [&str; 4] = ["PageHeader", "PageFooter", "Picture", "ListGroup"];
+
+// Options that control which blocks survive flattening
+#[derive(Debug, Clone)]
+pub struct FilterConfig {
+    pub excluded_types: Vec<String>,
+    // When set, only these types are kept and `excluded_types` is ignored
+    pub included_types: Option<Vec<String>>,
+    pub min_picture_area: f64,
+}
+
+impl FilterConfig {
+    /// Whether blocks of `block_type` survive flattening
+    /// ```
+    /// use pdf_parser::FilterConfig;
+    ///
+    /// let filter = FilterConfig::default();
+    /// assert!(filter.keeps_type("Text"));
+    /// assert!(!filter.keeps_type("PageHeader"));
+    ///
+    /// let only_text = FilterConfig {
+    ///     included_types: Some(vec!["Text".to_string()]),
+    ///     ..Default::default()
+    /// };
+    /// assert!(!only_text.keeps_type("SectionHeader"));
+    /// ```
+    pub fn keeps_type(&self, block_type: &str) -> bool {
+        match &self.included_types {
+            Some(included) => included.iter().any(|kept| kept == block_type),
+            None => !self.excluded_types.iter().any(|excluded| excluded == block_type),
+        }
+    }
+}
+
+impl ProcessingConfig {
+    /// Whether every enabled pass looks at one page at a time, so running
+    /// process_document on each page and concatenating the blocks gives the
+    /// same result as running it on the whole document. Blockquote and
+    /// boundary detection, table merging, footnote inlining and abbreviation
+    /// expansion all need the blocks of other pages.
+    /// ```
+    /// use pdf_parser::ProcessingConfig;
+    ///
+    /// assert!(ProcessingConfig::default().processes_pages_independently());
+    ///
+    /// let merging = ProcessingConfig { merge_split_tables: true, ..Default::default() };
+    /// assert!(!merging.processes_pages_independently());
+    /// ```
+    pub fn processes_pages_independently(&self) -> bool {
+        !(self.detect_blockquotes
+            || self.detect_doc_boundaries
+            || self.merge_split_tables
+            || self.inline_footnotes
+            || self.expand_abbreviations)
+    }
+}
+
+// Defaults match the command line with no flags
+impl Default for ProcessingConfig {
+    fn default() -> Self {
+        ProcessingConfig {
+            marker_bin: "marker_single".to_string(),
+            marker_batch_bin: "marker".to_string(),
+            keep_intermediate: false,
+            output_formats: vec![OutputFormat::Json],
+            yaml_front_matter: false,
+            auto_table_format: false,
+            content_hash: false,
+            group_by_section: false,
+            extract_index: false,
+            generate_report: false,
+            stats: false,
+            extract_tables: false,
+            sliding_window_summary: None,
+            strict_utf8: false,
+            preview: None,
+            preview_raw: false,
+            dedup: true,
+            flatten_lists: false,
+            detect_blockquotes: false,
+            detect_doc_boundaries: false,
+            split_at_boundaries: false,
+            merge_split_tables: false,
+            max_heading_depth: None,
+            inline_footnotes: false,
+            max_inline_footnote_length: 100,
+            expand_abbreviations: false,
+            flag_punctuation_noise: false,
+            drop_punctuation_noise: false,
+            punctuation_threshold: 0.15,
+            strip_control_chars: false,
+            strip_non_bmp: false,
+            base64_encode: false,
+            base64_decode: false,
+            base64_fields: vec![base64::Base64Field::Text],
+            validate_charset: None,
+            filter: FilterConfig::default(),
+        }
+    }
+}
+
+impl Default for FilterConfig {
+    fn default() -> Self {
+        FilterConfig {
+            excluded_types: DEFAULT_EXCLUDED_TYPES.iter().map(|t| t.to_string()).collect(),
+            included_types: None,
+            min_picture_area: 1000.0,
+        }
+    }
+}
+
+// Result of process_document: the processed blocks plus counts describing
+// what processing did
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct ProcessedDocument {
+    pub blocks: Vec<Block>,
+    // Blocks flattening looked at (see content_blocks)
+    pub input_block_count: usize,
+    pub output_block_count: usize,
+    pub block_type_histogram: BTreeMap<String, usize>,
+    // Kept pictures dropped for being smaller than the filter's min_picture_area
+    pub small_pictures_dropped: usize,
+    // Boundary blocks inserted by detect_doc_boundaries
+    pub document_boundaries: usize,
+    // Tables appended to the table before them by merge_split_tables
+    pub tables_merged: usize,
+    // Abbreviation → long form definitions found by expand_abbreviations,
+    // and the number of later uses expanded
+    pub abbreviations: BTreeMap<String, String>,
+    pub abbreviations_expanded: usize,
+    // Blocks that look like OCR punctuation noise. With drop_punctuation_noise
+    // they are no longer in `blocks`.
+    pub punctuation_flags: Vec<punctuation::PunctuationFlag>,
+}
+
+/// Runs the whole pipeline on a parsed Marker document: flattening,
+/// filtering and every post-processing pass enabled in `config`. Output
+/// options (formats, side outputs, base64 encoding) are left to the caller,
+/// and nothing is printed; what the passes found is in the result.
+/// ```
+/// use pdf_parser::{Block, Document, ProcessingConfig, process_document};
+///
+/// let document: Document = serde_json::from_str(
+///     r#"{"children": [{"id": "/page/0/Page/0", "block_type": "Page", "html": "", "children": [
+///         {"id": "/page/0/PageHeader/0", "block_type": "PageHeader", "html": "<p>Header</p>"},
+///         {"id": "/page/0/Text/1", "block_type": "Text", "html": "<p>Fish &amp; chips</p>"}
+///     ]}]}"#,
+/// )?;
+/// let processed = process_document(document, &ProcessingConfig::default());
+///
+/// let texts: Vec<&str> = processed.blocks.iter().map(|block: &Block| block.text.as_str()).collect();
+/// assert_eq!(texts, ["Fish & chips"]);
+/// assert_eq!((processed.input_block_count, processed.output_block_count), (2, 1));
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub fn process_document(document: Document, config: &ProcessingConfig) -> ProcessedDocument {
+    run_passes(document.children, config, true)
+}
+
+/// Runs the post-processing passes enabled in `config` on blocks that were
+/// already processed, as read back from --output-format json or toml
+/// output. The blocks are not flattened again and keep their text, edits
+/// included; blocks of types the filter excludes are still dropped.
+/// ```
+/// use pdf_parser::{Block, ProcessingConfig, reprocess_blocks};
+///
+/// let block = |block_type: &str, text: &str| Block {
+///     block_type: block_type.to_string(),
+///     html: "<p>Original</p>".to_string(),
+///     text: text.to_string(),
+///     ..Default::default()
+/// };
+/// let config = ProcessingConfig { strip_control_chars: true, ..Default::default() };
+/// let processed = reprocess_blocks(vec![block("PageHeader", "Header"), block("Text", "Edited\u{7}text")], &config);
+///
+/// let texts: Vec<&str> = processed.blocks.iter().map(|block| block.text.as_str()).collect();
+/// assert_eq!(texts, ["Edited text"]);
+/// ```
+pub fn reprocess_blocks(blocks: Vec<Block>, config: &ProcessingConfig) -> ProcessedDocument {
+    run_passes(blocks, config, false)
+}
+
+// The pipeline behind process_document and reprocess_blocks. Without
+// `flatten` the blocks are only filtered by type, not flattened.
+fn run_passes(blocks: Vec<Block>, config: &ProcessingConfig, flatten: bool) -> ProcessedDocument {
+    let input_block_count = content_blocks(&blocks).len();
+
+    let small_pictures_dropped = if config.filter.keeps_type("Picture") {
+        count_small_pictures(&blocks, config.filter.min_picture_area)
+    } else {
+        0
+    };
+
+    // Blockquote and boundary detection need the bboxes, page headers and
+    // footers that flattening strips
+    let blockquote_ids = if config.detect_blockquotes {
+        blockquote::detect_blockquotes(&blocks)
+    } else {
+        HashSet::new()
+    };
+    let document_boundaries = if config.detect_doc_boundaries {
+        boundaries::detect_boundaries(&blocks)
+    } else {
+        boundaries::Boundaries::default()
+    };
+
+    let mut filtered_blocks = if flatten {
+        flatten_and_filter_blocks(blocks, &config.filter)
+    } else {
+        blocks
+            .into_iter()
+            .filter(|block| {
+                config.filter.keeps_type(&block.block_type)
+                    && !is_small_picture(block, config.filter.min_picture_area)
+            })
+            .collect()
+    };
+
+    if config.detect_blockquotes {
+        blockquote::relabel_blockquotes(&mut filtered_blocks, &blockquote_ids);
+    }
+
+    let mut tables_merged = 0;
+    if config.merge_split_tables {
+        (filtered_blocks, tables_merged) = table_merge::merge_split_tables(filtered_blocks);
+    }
+
+    if config.flatten_lists {
+        lists::flatten_list_blocks(&mut filtered_blocks);
+    }
+
+    if let Some(max_level) = config.max_heading_depth {
+        filtered_blocks = headings::cap_heading_levels(filtered_blocks, max_level);
+    }
+
+    if config.inline_footnotes {
+        filtered_blocks =
+            footnotes::inline_footnotes(filtered_blocks, config.max_inline_footnote_length);
+    }
+
+    let mut abbreviations = BTreeMap::new();
+    let mut abbreviations_expanded = 0;
+    if config.expand_abbreviations {
+        let (long_forms, expanded) = abbreviations::expand_abbreviations(&mut filtered_blocks);
+        abbreviations.extend(long_forms);
+        abbreviations_expanded = expanded;
+    }
+
+    let mut punctuation_flags = Vec::new();
+    if config.flag_punctuation_noise || config.drop_punctuation_noise {
+        punctuation_flags =
+            punctuation::find_punctuation_noise(&filtered_blocks, config.punctuation_threshold);
+        if config.drop_punctuation_noise {
+            punctuation::drop_flagged_blocks(&mut filtered_blocks, &punctuation_flags);
+        }
+    }
+
+    if config.strip_control_chars {
+        sanitize::strip_control_chars(&mut filtered_blocks);
+    }
+
+    if config.strip_non_bmp {
+        sanitize::strip_non_bmp(&mut filtered_blocks);
+    }
+
+    let blocks = boundaries::insert_boundaries(filtered_blocks, &document_boundaries);
+    ProcessedDocument {
+        input_block_count,
+        output_block_count: blocks.len(),
+        block_type_histogram: block_type_histogram(&blocks),
+        blocks,
+        small_pictures_dropped,
+        document_boundaries: document_boundaries.len(),
+        tables_merged,
+        abbreviations,
+        abbreviations_expanded,
+        punctuation_flags,
+    }
+}
+
+/// The blocks flattening looks at: the children of each page, and any
+/// top-level block that is not a page
+/// ```
+/// use pdf_parser::{Block, content_blocks};
+///
+/// let text = Block { block_type: "Text".to_string(), ..Default::default() };
+/// let page = Block {
+///     block_type: "Page".to_string(),
+///     children: Some(vec![text.clone(), text.clone()]),
+///     ..Default::default()
+/// };
+/// assert_eq!(content_blocks(&[page, text]).len(), 3);
+/// ```
+pub fn content_blocks(blocks: &[Block]) -> Vec<&Block> {
+    blocks
+        .iter()
+        .flat_map(|block| {
+            if block.block_type == "Page" {
+                block.children.iter().flatten().collect()
+            } else {
+                vec![block]
+            }
+        })
+        .collect()
+}
+
+/// Number of blocks of each type
+/// ```
+/// use pdf_parser::{Block, block_type_histogram};
+///
+/// let block = |block_type: &str| Block { block_type: block_type.to_string(), ..Default::default() };
+/// let counts = block_type_histogram(&[block("Text"), block("Table"), block("Text")]);
+/// assert_eq!(counts["Text"], 2);
+/// assert_eq!(counts["Table"], 1);
+/// ```
+pub fn block_type_histogram(blocks: &[Block]) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for block in blocks {
+        *counts.entry(block.block_type.clone()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Replaces each page with its children, drops the block types `filter`
+/// does not keep and small pictures, and fills in `text` from the HTML. The
+/// kept blocks lose their geometry and children.
+/// ```
+/// use pdf_parser::{Block, FilterConfig, flatten_and_filter_blocks};
+///
+/// let page = Block {
+///     block_type: "Page".to_string(),
+///     children: Some(vec![
+///         Block { block_type: "PageFooter".to_string(), html: "<p>3</p>".to_string(), ..Default::default() },
+///         Block { block_type: "Text".to_string(), html: "<p>Body</p>".to_string(), ..Default::default() },
+///     ]),
+///     ..Default::default()
+/// };
+/// let blocks = flatten_and_filter_blocks(vec![page], &FilterConfig::default());
+/// assert_eq!(blocks.len(), 1);
+/// assert_eq!(blocks[0].text, "Body");
+/// ```
+pub fn flatten_and_filter_blocks(blocks: Vec<Block>, filter: &FilterConfig) -> Vec<Block> {
+    let mut result = Vec::new();
+    
+    for block in blocks {
+        // Skip page blocks as they are just containers
+        if block.block_type == "Page" {
+            // Process children of page blocks
+            if let Some(children) = block.children {
+                result.extend(flatten_and_filter_blocks(children, filter));
+            }
+        } else {
+            // Filter out excluded block types (by default header, footer, picture, and list group blocks)
+            if filter.keeps_type(&block.block_type)
+                && !is_small_picture(&block, filter.min_picture_area) {
+                // List groups only reference their items, so inline the items' HTML
+                let html = if block.block_type == "ListGroup" {
+                    lists::resolve_content_refs(&block.html, block.children.as_deref().unwrap_or(&[]))
+                } else {
+                    block.html
+                };
+
+                // Extract text from HTML
+                let text = extract_text_from_html(&html);
+                
+                // Remove polygon, bbox, children, section_hierarchy, and images fields
+                let filtered_block = Block {
+                    id: block.id,
+                    block_type: block.block_type,
+                    html,
+                    text,
+                    polygon: None,
+                    bbox: None,
+                    children: None,
+                    section_hierarchy: None,
+                    images: None,
+                    table_csv_path: None,
+                };
+                result.push(filtered_block);
+            }
+        }
+    }
+    
+    result
+}
+
+/// Whether a Picture block's bounding box is smaller than `min_area` (in
+/// square points). Marker bboxes are [x0, y0, x1, y1] corners, not
+/// [x, y, width, height]. Pictures without a bbox are never considered small.
+/// ```
+/// use pdf_parser::{Block, is_small_picture};
+///
+/// let icon = Block {
+///     block_type: "Picture".to_string(),
+///     bbox: Some(vec![100.0, 100.0, 110.0, 110.0]),
+///     ..Default::default()
+/// };
+/// assert!(is_small_picture(&icon, 1000.0));
+/// assert!(!is_small_picture(&icon, 50.0));
+/// ```
+pub fn is_small_picture(block: &Block, min_area: f64) -> bool {
+    if block.block_type != "Picture" {
+        return false;
+    }
+    match block.bbox.as_deref() {
+        Some([x0, y0, x1, y1, ..]) => (x1 - x0).abs() * (y1 - y0).abs() < min_area,
+        _ => false,
+    }
+}
+
+fn count_small_pictures(blocks: &[Block], min_area: f64) -> usize {
+    blocks
+        .iter()
+        .map(|block| {
+            let own = is_small_picture(block, min_area) as usize;
+            own + count_small_pictures(block.children.as_deref().unwrap_or(&[]), min_area)
+        })
+        .sum()
+}
+
+/// Page index encoded in a Marker block id such as "/page/3/Text/12"
+/// ```
+/// use pdf_parser::page_from_id;
+///
+/// assert_eq!(page_from_id("/page/3/Text/12"), Some(3));
+/// assert_eq!(page_from_id("custom-id"), None);
+/// ```
+pub fn page_from_id(id: &str) -> Option<u32> {
+    let mut parts = id.split('/').skip_while(|part| *part != "page");
+    parts.next()?;
+    parts.next()?.parse().ok()
+}
+
+/// Plain text of an HTML fragment: tags become spaces, character
+/// references are decoded and whitespace is collapsed
+/// ```
+/// use pdf_parser::extract_text_from_html;
+///
+/// assert_eq!(extract_text_from_html("<p>Smith &amp; <b>Jones</b>&#8212;1990</p>"), "Smith & Jones —1990");
+/// ```
+pub fn extract_text_from_html(html: &str) -> String {
+    // Create a regex to remove HTML tags
+    let re = Regex::new(r"<[^>]*>").unwrap();
+    
+    // Remove HTML tags
+    let text = re.replace_all(html, " ");
+
+    // Decode character references after the tags are gone, so an encoded
+    // `&lt;p&gt;` stays text instead of being stripped as a tag
+    let text = decode_html_entities(&text);
+    
+    // Clean up whitespace
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The first `max_length` characters of `text`, with an ellipsis when
+/// anything was cut off
+/// ```
+/// use pdf_parser::truncate_text;
+///
+/// assert_eq!(truncate_text("A rather long footnote", 8), "A rather…");
+/// assert_eq!(truncate_text("Short", 8), "Short");
+/// ```
+pub fn truncate_text(text: &str, max_length: usize) -> String {
+    if text.chars().count() <= max_length {
+        return text.to_string();
+    }
+    let truncated: String = text.chars().take(max_length).collect();
+    format!("{}…", truncated.trim_end())
+}
+
+/// Escapes text for use in HTML element content and quoted attribute values
+/// ```
+/// use pdf_parser::escape_html;
+///
+/// assert_eq!(escape_html("<a href=\"x\">&</a>"), "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;");
+/// ```
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Named character references decoded by decode_html_entities: the five XML
+// entities plus the ones common in Marker output
+const HTML_ENTITIES: [(&str, char); 32] = [
+    ("amp", '&'),
+    ("lt", '<'),
+    ("gt", '>'),
+    ("quot", '"'),
+    ("apos", '\''),
+    ("nbsp", '\u{a0}'),
+    ("shy", '\u{ad}'),
+    ("ndash", '–'),
+    ("mdash", '—'),
+    ("hellip", '…'),
+    ("lsquo", '‘'),
+    ("rsquo", '’'),
+    ("sbquo", '‚'),
+    ("ldquo", '“'),
+    ("rdquo", '”'),
+    ("bdquo", '„'),
+    ("laquo", '«'),
+    ("raquo", '»'),
+    ("iexcl", '¡'),
+    ("iquest", '¿'),
+    ("bull", '•'),
+    ("middot", '·'),
+    ("sect", '§'),
+    ("para", '¶'),
+    ("deg", '°'),
+    ("copy", '©'),
+    ("reg", '®'),
+    ("trade", '™'),
+    ("euro", '€'),
+    ("times", '×'),
+    ("divide", '÷'),
+    ("dagger", '†'),
+];
+
+// Replaces named (`&amp;`), decimal (`&#8212;`) and hexadecimal (`&#x2014;`)
+// character references with the characters they stand for. Unknown names
+// and numbers that are not valid characters are left as they are.
+fn decode_html_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let re = Regex::new(r"&(?:#([0-9]{1,7})|#[xX]([0-9a-fA-F]{1,6})|([A-Za-z][A-Za-z0-9]*));").unwrap();
+    re.replace_all(text, |caps: &regex::Captures| {
+        let decoded = if let Some(decimal) = caps.get(1) {
+            decimal.as_str().parse().ok().and_then(char::from_u32)
+        } else if let Some(hex) = caps.get(2) {
+            u32::from_str_radix(hex.as_str(), 16).ok().and_then(char::from_u32)
+        } else {
+            HTML_ENTITIES
+                .iter()
+                .find(|(name, _)| *name == &caps[3])
+                .map(|&(_, c)| c)
+        };
+        match decoded {
+            Some(c) if c != '\0' => c.to_string(),
+            _ => caps[0].to_string(),
+        }
+    })
+    .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn picture(bbox: Option<Vec<f64>>) -> Block {
+        Block {
+            id: "/page/0/Picture/0".to_string(),
+            block_type: "Picture".to_string(),
+            bbox,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn small_and_large_pictures_by_corner_bbox() {
+        // 20 x 30 = 600 pt² and 100 x 50 = 5000 pt²
+        assert!(is_small_picture(&picture(Some(vec![10.0, 10.0, 30.0, 40.0])), 1000.0));
+        assert!(!is_small_picture(&picture(Some(vec![100.0, 200.0, 200.0, 250.0])), 1000.0));
+        // Read as [x, y, width, height] this box would be tiny
+        assert!(!is_small_picture(&picture(Some(vec![300.0, 400.0, 350.0, 450.0])), 1000.0));
+    }
+
+    #[test]
+    fn threshold_is_exclusive_and_zero_keeps_everything() {
+        let exactly_1000 = picture(Some(vec![0.0, 0.0, 40.0, 25.0]));
+        assert!(!is_small_picture(&exactly_1000, 1000.0));
+        assert!(is_small_picture(&exactly_1000, 1000.5));
+        assert!(!is_small_picture(&picture(Some(vec![5.0, 5.0, 5.0, 5.0])), 0.0));
+    }
+
+    #[test]
+    fn missing_bboxes_and_other_types_are_never_small() {
+        assert!(!is_small_picture(&picture(None), 1000.0));
+        assert!(!is_small_picture(&picture(Some(vec![1.0, 2.0])), 1000.0));
+
+        let text = Block {
+            block_type: "Text".to_string(),
+            ..picture(Some(vec![0.0, 0.0, 1.0, 1.0]))
+        };
+        assert!(!is_small_picture(&text, 1000.0));
+    }
+
+    #[test]
+    fn flattening_drops_only_small_kept_pictures() {
+        let filter = FilterConfig {
+            excluded_types: Vec::new(),
+            ..Default::default()
+        };
+        let page = Block {
+            block_type: "Page".to_string(),
+            children: Some(vec![
+                picture(Some(vec![0.0, 0.0, 10.0, 10.0])),
+                Block {
+                    id: "/page/0/Picture/1".to_string(),
+                    ..picture(Some(vec![0.0, 0.0, 500.0, 300.0]))
+                },
+            ]),
+            ..Default::default()
+        };
+
+        let blocks = flatten_and_filter_blocks(vec![page.clone()], &filter);
+        let ids: Vec<&str> = blocks.iter().map(|block| block.id.as_str()).collect();
+        assert_eq!(ids, ["/page/0/Picture/1"]);
+        assert_eq!(count_small_pictures(&[page], filter.min_picture_area), 1);
+    }
+
+    #[test]
+    fn truncation_counts_characters_and_trims_before_the_ellipsis() {
+        assert_eq!(truncate_text("short", 5), "short");
+        assert_eq!(truncate_text("two words", 4), "two…");
+        assert_eq!(truncate_text("ñañá日本", 3), "ñañ…");
+    }
+
+    #[test]
+    fn html_escaping_covers_content_and_attributes() {
+        assert_eq!(
+            escape_html("<a href=\"x\">Tom & Jerry's</a>"),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+        );
+        assert_eq!(decode_html_entities(&escape_html("a < b & \"c\"")), "a < b & \"c\"");
+    }
+
+    #[test]
+    fn xml_and_numeric_entities_are_decoded() {
+        assert_eq!(
+            extract_text_from_html("<p>Smith &amp; Jones &lt;tag&gt; &quot;quoted&quot; &apos;single&apos;</p>"),
+            "Smith & Jones <tag> \"quoted\" 'single'"
+        );
+        assert_eq!(extract_text_from_html("1990&#8211;2000&#x2014;today"), "1990–2000—today");
+        assert_eq!(extract_text_from_html("&#X2014;&#x1F600;"), "—😀");
+        // No-break spaces are whitespace, so they collapse like any other
+        assert_eq!(extract_text_from_html("a&#160;b &nbsp; c"), "a b c");
+    }
+
+    #[test]
+    fn entities_round_trip_through_escaping() {
+        for text in ["Smith & Jones", "a < b > c", "\"quoted\" and 'single'", "&amp; literally"] {
+            assert_eq!(extract_text_from_html(&format!("<p>{}</p>", escape_html(text))), text);
+        }
+    }
+
+    #[test]
+    fn encoded_markup_is_kept_as_text() {
+        // Decoding happens after tags are stripped, so an encoded tag is text
+        assert_eq!(extract_text_from_html("<p>Use &lt;p&gt; for paragraphs</p>"), "Use <p> for paragraphs");
+    }
+
+    #[test]
+    fn unknown_and_invalid_references_are_left_alone() {
+        assert_eq!(
+            extract_text_from_html("&bogus; &#0; &#xD800; &#99999999; AT&T & co"),
+            "&bogus; &#0; &#xD800; &#99999999; AT&T & co"
+        );
+    }
+
+    #[test]
+    fn flattened_blocks_carry_decoded_text() {
+        let page = Block {
+            block_type: "Page".to_string(),
+            children: Some(vec![Block {
+                id: "/page/0/Text/0".to_string(),
+                block_type: "Text".to_string(),
+                html: "<p>Smith &amp; Jones&#8212;since 1990 &copy;</p>".to_string(),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+
+        let blocks = flatten_and_filter_blocks(vec![page], &FilterConfig::default());
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].text, "Smith & Jones—since 1990 ©");
+        // The HTML is passed through untouched
+        assert_eq!(blocks[0].html, "<p>Smith &amp; Jones&#8212;since 1990 &copy;</p>");
+    }
+}
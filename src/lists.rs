@@ -0,0 +1,300 @@
+use crate::{Block, extract_text_from_html};
+use regex::Regex;
+use scraper::{ElementRef, Html, Node};
+
+const BULLETS: [&str; 3] = ["•", "◦", "▪"];
+
+#[derive(Clone, Copy)]
+enum ListKind {
+    Unordered,
+    Ordered,
+}
+
+/// Replaces each `<content-ref src='...'>` in a container's HTML with the HTML
+/// of the referenced child block, recursively, so a ListGroup carries its
+/// items inline
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::lists::resolve_content_refs;
+///
+/// let item = Block {
+///     id: "/page/0/ListItem/1".to_string(),
+///     html: "<li>First</li>".to_string(),
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     resolve_content_refs("<ul><content-ref src='/page/0/ListItem/1'></content-ref></ul>", &[item]),
+///     "<ul><li>First</li></ul>"
+/// );
+/// ```
+pub fn resolve_content_refs(html: &str, children: &[Block]) -> String {
+    let re = Regex::new(r#"<content-ref\s+src=['"]([^'"]*)['"]\s*>\s*</content-ref>"#).unwrap();
+    re.replace_all(html, |caps: &regex::Captures| {
+        match children.iter().find(|child| child.id == caps[1]) {
+            Some(child) => {
+                let grandchildren = child.children.as_deref().unwrap_or(&[]);
+                resolve_content_refs(&child.html, grandchildren)
+            }
+            None => String::new(),
+        }
+    })
+    .into_owned()
+}
+
+/// Renders (possibly nested) <ul>/<ol> markup as plain text lines with a
+/// depth-dependent prefix: `•`, `◦`, `▪` for unordered lists and `1.`, `a.`,
+/// `i.` for ordered ones, indented by two spaces per level
+/// ```
+/// use pdf_parser::lists::flatten_nested_list;
+///
+/// assert_eq!(
+///     flatten_nested_list("<ol><li>Setup<ul><li>Install</li></ul></li><li>Run</li></ol>"),
+///     "1. Setup\n  ◦ Install\n2. Run"
+/// );
+/// ```
+pub fn flatten_nested_list(html: &str) -> String {
+    let fragment = Html::parse_fragment(html);
+    let mut lines = Vec::new();
+    // Open lists with the number of items seen so far in each
+    let mut stack: Vec<(ListKind, usize)> = Vec::new();
+    flatten_children(fragment.root_element(), &mut stack, &mut lines);
+
+    if lines.is_empty() {
+        extract_text_from_html(html)
+    } else {
+        lines.join("\n")
+    }
+}
+
+/// Sets the text of every ListGroup block to its flattened list rendering
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::lists::flatten_list_blocks;
+///
+/// let mut blocks = [Block {
+///     block_type: "ListGroup".to_string(),
+///     html: "<ul><li>One</li><li>Two</li></ul>".to_string(),
+///     ..Default::default()
+/// }];
+/// flatten_list_blocks(&mut blocks);
+/// assert_eq!(blocks[0].text, "• One\n• Two");
+/// ```
+pub fn flatten_list_blocks(blocks: &mut [Block]) {
+    for block in blocks.iter_mut().filter(|block| block.block_type == "ListGroup") {
+        block.text = flatten_nested_list(&block.html);
+    }
+}
+
+// Adds a line for every <li> among the descendants of `element`, each
+// followed by the lines of the lists nested in it
+fn flatten_children(element: ElementRef, stack: &mut Vec<(ListKind, usize)>, lines: &mut Vec<String>) {
+    for child in element.children().filter_map(ElementRef::wrap) {
+        match child.value().name() {
+            "ul" | "ol" => {
+                let kind = if child.value().name() == "ol" {
+                    ListKind::Ordered
+                } else {
+                    ListKind::Unordered
+                };
+                stack.push((kind, 0));
+                flatten_children(child, stack, lines);
+                stack.pop();
+            }
+            "li" => {
+                let depth = stack.len().saturating_sub(1);
+                let prefix = match stack.last_mut() {
+                    Some((kind, count)) => {
+                        *count += 1;
+                        item_prefix(*kind, depth, *count)
+                    }
+                    // A stray <li> outside any list is treated as a bullet
+                    None => item_prefix(ListKind::Unordered, 0, 1),
+                };
+                let mut text = String::new();
+                item_text(child, &mut text);
+                let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                if !text.is_empty() {
+                    lines.push(format!("{}{}", prefix, text));
+                }
+                flatten_children(child, stack, lines);
+            }
+            _ => flatten_children(child, stack, lines),
+        }
+    }
+}
+
+// Text of an item without the lists nested in it. Tags become spaces, as in
+// extract_text_from_html.
+fn item_text(element: ElementRef, text: &mut String) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(content) => text.push_str(content),
+            Node::Element(tag) => {
+                text.push(' ');
+                if !matches!(tag.name(), "ul" | "ol") {
+                    item_text(ElementRef::wrap(child).unwrap(), text);
+                }
+                text.push(' ');
+            }
+            _ => {}
+        }
+    }
+}
+
+fn item_prefix(kind: ListKind, depth: usize, number: usize) -> String {
+    let indent = "  ".repeat(depth);
+    match kind {
+        ListKind::Unordered => format!("{}{} ", indent, BULLETS[depth % BULLETS.len()]),
+        ListKind::Ordered => {
+            let marker = match depth % 3 {
+                0 => number.to_string(),
+                1 => alphabetic(number),
+                _ => roman(number),
+            };
+            format!("{}{}. ", indent, marker)
+        }
+    }
+}
+
+// 1 -> a, 26 -> z, 27 -> aa
+fn alphabetic(mut number: usize) -> String {
+    let mut letters = Vec::new();
+    while number > 0 {
+        number -= 1;
+        letters.push((b'a' + (number % 26) as u8) as char);
+        number /= 26;
+    }
+    letters.iter().rev().collect()
+}
+
+fn roman(mut number: usize) -> String {
+    const NUMERALS: [(usize, &str); 13] = [
+        (1000, "m"),
+        (900, "cm"),
+        (500, "d"),
+        (400, "cd"),
+        (100, "c"),
+        (90, "xc"),
+        (50, "l"),
+        (40, "xl"),
+        (10, "x"),
+        (9, "ix"),
+        (5, "v"),
+        (4, "iv"),
+        (1, "i"),
+    ];
+
+    let mut result = String::new();
+    for (value, numeral) in NUMERALS {
+        while number >= value {
+            result.push_str(numeral);
+            number -= value;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(id: &str, block_type: &str, html: &str) -> Block {
+        Block {
+            id: id.to_string(),
+            block_type: block_type.to_string(),
+            html: html.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn flat_unordered_list() {
+        let html = "<ul><li>Apples</li><li>Pears</li></ul>";
+        assert_eq!(flatten_nested_list(html), "• Apples\n• Pears");
+    }
+
+    #[test]
+    fn three_levels_of_unordered_lists() {
+        let html = "<ul><li>Fruit<ul><li>Citrus<ul><li>Lemon</li><li>Lime</li></ul></li>\
+                    <li>Berries</li></ul></li><li>Vegetables</li></ul>";
+        assert_eq!(
+            flatten_nested_list(html),
+            "• Fruit\n  ◦ Citrus\n    ▪ Lemon\n    ▪ Lime\n  ◦ Berries\n• Vegetables"
+        );
+    }
+
+    #[test]
+    fn three_levels_of_ordered_lists_number_per_depth() {
+        let html = "<ol><li>One<ol><li>Alpha<ol><li>First</li><li>Second</li><li>Third</li>\
+                    <li>Fourth</li></ol></li><li>Beta</li></ol></li><li>Two</li></ol>";
+        assert_eq!(
+            flatten_nested_list(html),
+            "1. One\n  a. Alpha\n    i. First\n    ii. Second\n    iii. Third\n    iv. Fourth\n  b. Beta\n2. Two"
+        );
+    }
+
+    #[test]
+    fn mixed_list_kinds_and_markup_inside_items() {
+        let html = "<OL><LI><b>Setup</b><ul><li>Install &amp; configure</li></ul></LI></OL>";
+        assert_eq!(flatten_nested_list(html), "1. Setup\n  ◦ Install & configure");
+    }
+
+    #[test]
+    fn markup_is_parsed_as_html_not_scanned_for_tags() {
+        // A `>` inside an attribute value does not end the tag
+        let html = "<ul><li title='a > b'>Quoted</li><li data-note=\"<ol>\">Attribute</li></ul>";
+        assert_eq!(flatten_nested_list(html), "• Quoted\n• Attribute");
+        // Unclosed items end at the next item or the end of their list
+        let html = "<ol><li>First<li>Second<ul><li>Nested</ul><li>Third</ol>";
+        assert_eq!(flatten_nested_list(html), "1. First\n2. Second\n  ◦ Nested\n3. Third");
+    }
+
+    #[test]
+    fn text_after_a_nested_list_stays_with_its_item() {
+        let html = "<ul><li>Before<ul><li>Inner</li></ul>after</li></ul>";
+        assert_eq!(flatten_nested_list(html), "• Before after\n  ◦ Inner");
+    }
+
+    #[test]
+    fn markup_without_items_falls_back_to_plain_text() {
+        assert_eq!(flatten_nested_list("<p>No list here</p>"), "No list here");
+    }
+
+    #[test]
+    fn content_refs_are_resolved_recursively() {
+        let nested = Block {
+            children: Some(vec![block("/page/0/ListItem/2", "ListItem", "<li>Inner</li>")]),
+            ..block(
+                "/page/0/ListItem/1",
+                "ListItem",
+                "<li>Outer<ul><content-ref src='/page/0/ListItem/2'></content-ref></ul></li>",
+            )
+        };
+        let html = "<ul><content-ref src='/page/0/ListItem/1'></content-ref>\
+                    <content-ref src=\"/page/0/ListItem/9\"></content-ref></ul>";
+
+        let resolved = resolve_content_refs(html, &[nested]);
+        assert_eq!(resolved, "<ul><li>Outer<ul><li>Inner</li></ul></li></ul>");
+        assert_eq!(flatten_nested_list(&resolved), "• Outer\n  ◦ Inner");
+    }
+
+    #[test]
+    fn only_list_groups_are_flattened() {
+        let mut blocks = [
+            block("/page/0/ListGroup/0", "ListGroup", "<ul><li>A</li><li>B</li></ul>"),
+            block("/page/0/Text/1", "Text", "<ul><li>C</li></ul>"),
+        ];
+        flatten_list_blocks(&mut blocks);
+        assert_eq!(blocks[0].text, "• A\n• B");
+        assert_eq!(blocks[1].text, "");
+    }
+
+    #[test]
+    fn alphabetic_and_roman_markers() {
+        assert_eq!(alphabetic(1), "a");
+        assert_eq!(alphabetic(26), "z");
+        assert_eq!(alphabetic(27), "aa");
+        assert_eq!(roman(4), "iv");
+        assert_eq!(roman(1994), "mcmxciv");
+    }
+}
@@ -1,10 +1,25 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use glob::glob;
-use regex::Regex;
-use serde::{Deserialize, Serialize};
-use std::fs::{self, File};
-use std::io::Write;
+use pdf_parser::{
+    Block, Document, FilterConfig, OutputFormat, ProcessedDocument, ProcessingConfig, DEFAULT_EXCLUDED_TYPES, base64,
+    boundaries, dedup, encoding, fingerprint, front_matter, index_terms, io, markdown, ngrams,
+    page_summary, parquet_format, process_document, reprocess_blocks, sections, stats, streaming, summarize, tables,
+    toml_format,
+};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::BufReader;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+mod citation_graph;
+mod corpus;
+mod marker;
+mod report;
+mod schema;
+mod telemetry;
 
 #[derive(Parser, Debug)]
 #[clap(
@@ -30,82 +45,398 @@ Processing includes:
 3. Removing unnecessary data fields (polygon, bbox, children, section_hierarchy, images)
 4. Extracting plain text content from HTML markup
 
-Output will be saved in the same directory as the input file with '_processed' appended to the filename, unless a custom output directory is specified with the -o flag."
+Output will be saved in the same directory as the input file with '_processed' appended to the filename, unless a custom output directory is specified with the -o flag.",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
 )]
 struct Args {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
     /// Input path (PDF file, directory of PDFs, or JSON file)
-    input: String,
+    #[clap(required = true)]
+    input: Option<String>,
 
     /// Output directory (optional)
     #[clap(short, long)]
     output_dir: Option<String>,
+
+    /// Marker executable run on PDF input to produce its JSON
+    #[clap(long, default_value = "marker_single")]
+    marker_bin: String,
+
+    /// Marker batch executable run once over the PDFs of a directory input
+    #[clap(long, default_value = "marker")]
+    marker_batch_bin: String,
+
+    /// Copy the JSON Marker writes for each PDF next to its processed output as <stem>.json
+    #[clap(long)]
+    keep_intermediate: bool,
+
+    /// Format of the processed output file
+    #[clap(long, visible_alias = "format", value_enum, default_value_t = OutputFormat::Json)]
+    output_format: OutputFormat,
+
+    /// Write several formats in one pass (comma-separated, e.g. json,text,markdown) instead of --output-format
+    #[clap(long, value_enum, value_delimiter = ',', conflicts_with = "output_format")]
+    multi_format: Vec<OutputFormat>,
+
+    /// Prepend YAML front matter (title, date, source file) to Markdown output
+    #[clap(long)]
+    yaml_front_matter: bool,
+
+    /// Render each Markdown table as a definition list, pipe table or ASCII grid depending on its column count
+    #[clap(long)]
+    auto_table_format: bool,
+
+    /// Print a SHA-256 fingerprint of the processed content and save it to <stem>.sha256 next to the output
+    #[clap(long)]
+    content_hash: bool,
+
+    /// Comma-separated block types to drop, replacing the default list (PageHeader,PageFooter,Picture,ListGroup)
+    #[clap(long, value_delimiter = ',', value_name = "TYPES")]
+    exclude_types: Vec<String>,
+
+    /// Comma-separated block types to keep; every other type is dropped
+    #[clap(long, value_delimiter = ',', value_name = "TYPES", conflicts_with = "exclude_types")]
+    include_only_types: Vec<String>,
+
+    /// Keep ListGroup blocks (with their list items inlined) instead of dropping them
+    #[clap(long, conflicts_with = "include_only_types")]
+    keep_list_groups: bool,
+
+    /// Keep Picture blocks instead of dropping them
+    #[clap(long, conflicts_with = "include_only_types")]
+    keep_pictures: bool,
+
+    /// Drop kept Picture blocks whose bounding box is smaller than this many square points (0 keeps them all)
+    #[clap(long, default_value_t = 1000.0)]
+    min_picture_area: f64,
+
+    /// Render kept ListGroup blocks as indented text lines with bullet or number prefixes
+    #[clap(long, requires = "keep_list_groups")]
+    flatten_lists: bool,
+
+    /// Write <stem>_report.html summarizing the run: block counts, file sizes, timing, pages, previews and dropped blocks
+    #[clap(long)]
+    generate_report: bool,
+
+    /// Write <stem>_stats.json (block counts by type before and after, dropped types, timing) next to each output, and _summary_stats.json for directory runs
+    #[clap(long)]
+    stats: bool,
+
+    /// Write each Table block to <stem>_table_<n>.csv (n counts tables from 0) and replace its text with tab-separated rows
+    #[clap(long)]
+    extract_tables: bool,
+
+    /// Relabel quoted, indented or introduced ("... wrote:") Text blocks as BlockQuote
+    #[clap(long)]
+    detect_blockquotes: bool,
+
+    /// Insert DocumentBoundary blocks where a combined file moves on to the next source document (page numbering restarting at 1, page size changes, large gaps before a heading)
+    #[clap(long)]
+    detect_doc_boundaries: bool,
+
+    /// Write each document found by --detect-doc-boundaries to its own <stem>_docN_processed file
+    #[clap(long, requires = "detect_doc_boundaries")]
+    split_at_boundaries: bool,
+
+    /// Merge adjacent Table blocks that start with the same header row into one table
+    #[clap(long)]
+    merge_split_tables: bool,
+
+    /// Promote headings deeper than level N (h4-h6 with N=3) to level N
+    #[clap(long, value_name = "N", value_parser = clap::value_parser!(u8).range(1..=6))]
+    max_heading_depth: Option<u8>,
+
+    /// Replace [N] footnote markers with the text of the matching Footnote block and drop the inlined footnotes
+    #[clap(long)]
+    inline_footnotes: bool,
+
+    /// Maximum number of footnote characters inlined by --inline-footnotes
+    #[clap(long, default_value_t = 100)]
+    max_inline_footnote_length: usize,
+
+    /// Expand abbreviations defined as "Long Form (ABBREV)" wherever they are used later in the document
+    #[clap(long)]
+    expand_abbreviations: bool,
+
+    /// Replace control characters (except newline, carriage return and tab) and private-use characters in text with spaces
+    #[clap(long)]
+    strip_control_chars: bool,
+
+    /// Remove characters outside the Basic Multilingual Plane (U+0000-U+FFFF) from text
+    #[clap(long)]
+    strip_non_bmp: bool,
+
+    /// Replace the --base64-fields of every output block with their base64 encoding (applied after all other processing)
+    #[clap(long)]
+    base64_encode_text: bool,
+
+    /// Decode base64-encoded --base64-fields in the input before any other processing. Use it on processed JSON or TOML output (e.g. from --base64-encode-text), which is read back as is; in Marker JSON the text is rebuilt from html
+    #[clap(long)]
+    base64_decode: bool,
+
+    /// Comma-separated block fields encoded by --base64-encode-text and decoded by --base64-decode
+    #[clap(long, value_enum, value_delimiter = ',', default_value = "text")]
+    base64_fields: Vec<base64::Base64Field>,
+
+    /// Before writing, report blocks whose text has characters that cannot be represented in --encoding
+    #[clap(long)]
+    validate_charset: bool,
+
+    /// Target encoding checked by --validate-charset
+    #[clap(long, value_enum, default_value = "latin1", requires = "validate_charset")]
+    encoding: encoding::Encoding,
+
+    /// Report blocks whose punctuation ratio exceeds --punctuation-threshold or that contain long punctuation runs
+    #[clap(long)]
+    flag_punctuation_noise: bool,
+
+    /// Drop the blocks reported by --flag-punctuation-noise from the output
+    #[clap(long)]
+    drop_punctuation_noise: bool,
+
+    /// Punctuation-to-character ratio above which a block is considered noise
+    #[clap(long, default_value_t = 0.15)]
+    punctuation_threshold: f64,
+
+    /// Also write <stem>_grouped.json with blocks grouped into { header, body } sections
+    #[clap(long)]
+    group_by_section: bool,
+
+    /// Also write <stem>_index.json with `term, page, page-page` entries found in the last 5% of blocks
+    #[clap(long)]
+    extract_index: bool,
+
+    /// Also write <stem>_summary.txt, an extractive summary built from overlapping windows of blocks
+    #[clap(long)]
+    sliding_window_summary: bool,
+
+    /// Number of blocks in each --sliding-window-summary window
+    #[clap(long, default_value_t = 50)]
+    window_size: usize,
+
+    /// Number of blocks shared by consecutive summary windows
+    #[clap(long, default_value_t = 10)]
+    window_overlap: usize,
+
+    /// Number of sentences selected from each summary window
+    #[clap(long, default_value_t = 3)]
+    summary_sentences: usize,
+
+    /// Fail on input files that are not valid UTF-8 instead of decoding them as Latin-1/Windows-1252
+    #[clap(long)]
+    strict_utf8: bool,
+
+    /// Print the first N processed blocks to stdout instead of writing output files
+    #[clap(long, value_name = "N")]
+    preview: Option<usize>,
+
+    /// With --preview, show the raw Marker block next to each processed block
+    #[clap(long, requires = "preview")]
+    preview_raw: bool,
+
+    /// Process every file in a directory run, even if its content is identical to one already processed
+    #[clap(long)]
+    no_dedup: bool,
+
+    /// OTLP/gRPC endpoint that a trace span per processed file is exported to
+    #[cfg(feature = "otel")]
+    #[clap(long, value_name = "URL", default_value = telemetry::DEFAULT_ENDPOINT)]
+    otel_endpoint: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct Block {
-    #[serde(default)]
-    id: String,
-    #[serde(default)]
-    block_type: String,
-    #[serde(default)]
-    html: String,
-    #[serde(default)]
-    text: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    polygon: Option<Vec<Vec<f64>>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    bbox: Option<Vec<f64>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    children: Option<Vec<Block>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    section_hierarchy: Option<serde_json::Value>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    images: Option<serde_json::Value>,
-}
-
-impl Default for Block {
-    fn default() -> Self {
-        Block {
-            id: String::new(),
-            block_type: String::new(),
-            html: String::new(),
-            text: String::new(),
-            polygon: None,
-            bbox: None,
-            children: None,
-            section_hierarchy: None,
-            images: None,
-        }
-    }
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-struct Document {
-    children: Vec<Block>,
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Validate a processed JSON file against a JSON Schema (exits with 1 on violations)
+    ValidateOutput {
+        /// Processed JSON file to validate
+        processed: String,
+
+        /// JSON Schema to validate against (defaults to the built-in block schema)
+        #[clap(long)]
+        schema: Option<String>,
+    },
+
+    /// Count the words used in section headings across a directory of processed files
+    HeadingFrequency {
+        /// Directory containing *_processed.json files (searched recursively)
+        output_dir: String,
+
+        /// Only keep the N most frequent words
+        #[clap(long)]
+        top_k: Option<usize>,
+
+        /// Only keep words that appear in at least N documents
+        #[clap(long, default_value_t = 1)]
+        min_df: usize,
+    },
+
+    /// Build a citation graph (citation_graph.json) from the *_citations.json files in a directory
+    CitationGraph {
+        /// Directory containing *_processed.json and *_citations.json files (searched recursively)
+        output_dir: String,
+
+        /// Minimum Jaccard similarity between a citation and a document title to link them
+        #[clap(long, default_value_t = 0.5)]
+        min_similarity: f64,
+    },
+
+    /// Write fixed-length character n-grams of a processed file's text to <stem>_ngrams.jsonl
+    Ngrams {
+        /// Processed JSON file to read
+        processed: String,
+
+        /// Number of characters in each n-gram
+        #[clap(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+        n: usize,
+
+        /// Number of characters between the starts of consecutive n-grams
+        #[clap(long, default_value_t = 1, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+        stride: usize,
+
+        /// Skip blocks with fewer than M characters of text
+        #[clap(long, value_name = "M", default_value_t = 0)]
+        min_chars: usize,
+    },
+
+    /// Summarize each page of a processed file (block counts by type, words, tables, headings, images) into <stem>_pages.json
+    PageSummary {
+        /// Processed JSON file to read
+        processed: String,
+    },
 }
 
 // Struct to track unprocessed files
-#[derive(Debug)]
+#[derive(Serialize, Debug)]
 struct UnprocessedFile {
     path: String,
     reason: String,
 }
 
+// Totals over a directory run, written to _summary_stats.json by --stats
+#[derive(Serialize, Debug, Default)]
+struct SummaryStats<'a> {
+    files_processed: usize,
+    files_skipped_duplicate: usize,
+    files_failed: usize,
+    total_blocks_before: usize,
+    total_blocks_after: usize,
+    blocks_by_type_before: BTreeMap<String, usize>,
+    blocks_by_type_after: BTreeMap<String, usize>,
+    processing_time_ms: u64,
+    unprocessed_files: &'a [UnprocessedFile],
+}
+
+impl SummaryStats<'_> {
+    fn add(&mut self, file_stats: &stats::ProcessingStats) {
+        self.files_processed += 1;
+        self.total_blocks_before += file_stats.total_blocks_before;
+        self.total_blocks_after += file_stats.total_blocks_after;
+        for (block_type, count) in &file_stats.blocks_by_type_before {
+            *self.blocks_by_type_before.entry(block_type.clone()).or_insert(0) += count;
+        }
+        for (block_type, count) in &file_stats.blocks_by_type_after {
+            *self.blocks_by_type_after.entry(block_type.clone()).or_insert(0) += count;
+        }
+        self.processing_time_ms += file_stats.processing_time_ms;
+    }
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
-    let input_path = Path::new(&args.input);
+
+    if let Some(command) = args.command {
+        return run_command(command);
+    }
+
+    #[cfg(feature = "otel")]
+    telemetry::init(&args.otel_endpoint)?;
+
+    let filter = filter_config(&args);
+
+    let mut output_formats = Vec::new();
+    if args.multi_format.is_empty() {
+        output_formats.push(args.output_format);
+    } else {
+        for format in args.multi_format {
+            if !output_formats.contains(&format) {
+                output_formats.push(format);
+            }
+        }
+    }
+
+    if args.yaml_front_matter && !output_formats.contains(&OutputFormat::Markdown) {
+        eprintln!("--yaml-front-matter can only be used with --output-format markdown");
+        std::process::exit(1);
+    }
+
+    let config = ProcessingConfig {
+        marker_bin: args.marker_bin,
+        marker_batch_bin: args.marker_batch_bin,
+        keep_intermediate: args.keep_intermediate,
+        output_formats,
+        yaml_front_matter: args.yaml_front_matter,
+        auto_table_format: args.auto_table_format,
+        content_hash: args.content_hash,
+        group_by_section: args.group_by_section,
+        extract_index: args.extract_index,
+        generate_report: args.generate_report,
+        stats: args.stats,
+        extract_tables: args.extract_tables,
+        sliding_window_summary: args.sliding_window_summary.then_some(summarize::SlidingWindow {
+            size: args.window_size,
+            overlap: args.window_overlap,
+            sentences_per_window: args.summary_sentences,
+        }),
+        strict_utf8: args.strict_utf8,
+        preview: args.preview,
+        preview_raw: args.preview_raw,
+        // Previews write nothing and split documents are not named after the
+        // input, so in both cases there is no earlier output to copy
+        dedup: !args.no_dedup && args.preview.is_none() && !args.split_at_boundaries,
+        flatten_lists: args.flatten_lists,
+        detect_blockquotes: args.detect_blockquotes,
+        detect_doc_boundaries: args.detect_doc_boundaries,
+        split_at_boundaries: args.split_at_boundaries,
+        merge_split_tables: args.merge_split_tables,
+        max_heading_depth: args.max_heading_depth,
+        inline_footnotes: args.inline_footnotes,
+        max_inline_footnote_length: args.max_inline_footnote_length,
+        expand_abbreviations: args.expand_abbreviations,
+        flag_punctuation_noise: args.flag_punctuation_noise,
+        drop_punctuation_noise: args.drop_punctuation_noise,
+        punctuation_threshold: args.punctuation_threshold,
+        strip_control_chars: args.strip_control_chars,
+        strip_non_bmp: args.strip_non_bmp,
+        base64_encode: args.base64_encode_text,
+        base64_decode: args.base64_decode,
+        base64_fields: args.base64_fields,
+        validate_charset: args.validate_charset.then_some(args.encoding),
+        filter,
+    };
+
+    let input = args.input.expect("clap requires an input path when no subcommand is given");
+    let input_path = Path::new(&input);
 
     if input_path.is_file() {
-        if input_path.extension().and_then(|ext| ext.to_str()) == Some("json") {
-            match process_json_file(input_path, &args.output_dir) {
+        if matches!(
+            input_path.extension().and_then(|ext| ext.to_str()),
+            Some("json") | Some("toml")
+        ) {
+            match process_json_file(input_path, &args.output_dir, &config) {
                 Ok(_) => (),
                 Err(e) => {
                     eprintln!("Error processing file {:?}: {}", input_path, e);
+                    telemetry::shutdown();
                     std::process::exit(1);
                 }
             }
-        } else {
-            process_pdf_file(input_path, &args.output_dir)?;
+        } else if let Err(e) = process_pdf_file(input_path, &args.output_dir, &config) {
+            eprintln!("Error processing file {:?}: {}", input_path, e);
+            telemetry::shutdown();
+            std::process::exit(1);
         }
     } else if input_path.is_dir() {
         // For directory input, we need to determine the output directory
@@ -122,11 +453,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             parent_dir.join(processed_dir_name).to_string_lossy().to_string()
         };
         
-        let unprocessed_files = process_pdf_directory_with_structure(input_path, &output_dir)?;
+        let result = process_pdf_directory_with_structure(input_path, &output_dir, &config);
+        telemetry::shutdown();
+        let unprocessed_files = result?;
         if !unprocessed_files.is_empty() {
-            println!("\nUnprocessed files:");
+            eprintln!("\nUnprocessed files:");
             for file in unprocessed_files {
-                println!("  {}: {}", file.path, file.reason);
+                eprintln!("  {}: {}", file.path, file.reason);
             }
         }
     } else {
@@ -134,129 +467,993 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         std::process::exit(1);
     }
 
+    telemetry::shutdown();
+    Ok(())
+}
+
+fn run_command(command: Command) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        Command::ValidateOutput { processed, schema } => {
+            let valid = validate_output(Path::new(&processed), schema.as_deref().map(Path::new))?;
+            if !valid {
+                std::process::exit(1);
+            }
+        }
+        Command::HeadingFrequency {
+            output_dir,
+            top_k,
+            min_df,
+        } => heading_frequency(Path::new(&output_dir), top_k, min_df)?,
+        Command::CitationGraph {
+            output_dir,
+            min_similarity,
+        } => citation_graph(Path::new(&output_dir), min_similarity)?,
+        Command::Ngrams {
+            processed,
+            n,
+            stride,
+            min_chars,
+        } => write_ngrams(Path::new(&processed), n, stride, min_chars)?,
+        Command::PageSummary { processed } => page_summary(Path::new(&processed))?,
+    }
+
+    Ok(())
+}
+
+fn validate_output(
+    processed_path: &Path,
+    schema_path: Option<&Path>,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let schema_content = match schema_path {
+        Some(path) => fs::read_to_string(path)?,
+        None => schema::DEFAULT_BLOCK_SCHEMA.to_string(),
+    };
+    let schema: serde_json::Value = match serde_json::from_str(&schema_content) {
+        Ok(schema) => schema,
+        Err(e) => return Err(format!("Invalid JSON in schema {:?}: {}", schema_path, e).into()),
+    };
+
+    let processed_content = fs::read_to_string(processed_path)?;
+    let processed: serde_json::Value = match serde_json::from_str(&processed_content) {
+        Ok(processed) => processed,
+        Err(e) => return Err(format!("Invalid JSON in {:?}: {}", processed_path, e).into()),
+    };
+
+    let violations = schema::validate(&schema, &processed);
+    if violations.is_empty() {
+        println!("{:?} is valid", processed_path);
+        return Ok(true);
+    }
+
+    println!("{:?} failed validation:", processed_path);
+    for violation in &violations {
+        println!("  {}: {}", violation.path, violation.message);
+    }
+    println!("{} violation(s) found", violations.len());
+    Ok(false)
+}
+
+fn heading_frequency(
+    output_dir: &Path,
+    top_k: Option<usize>,
+    min_df: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let files = corpus::find_processed_files(output_dir)?;
+    eprintln!("Reading {} processed files from {:?}", files.len(), output_dir);
+
+    let mut documents = Vec::new();
+    for file in &files {
+        match corpus::read_processed_blocks(file) {
+            Ok(blocks) => documents.push(blocks),
+            Err(e) => eprintln!("Skipping {:?}: {}", file, e),
+        }
+    }
+
+    let words = corpus::heading_word_frequencies(&documents, top_k, min_df);
+
+    let output_path = output_dir.join("heading_words.json");
+    let words_json = serde_json::to_string_pretty(&words)?;
+    io::atomic_write(&output_path, words_json.as_bytes())?;
+
+    eprintln!("Heading word frequencies saved to: {:?}", output_path);
+    Ok(())
+}
+
+fn citation_graph(output_dir: &Path, min_similarity: f64) -> Result<(), Box<dyn std::error::Error>> {
+    let graph = citation_graph::build_citation_graph(output_dir, min_similarity)?;
+    for (path, reason) in &graph.skipped {
+        eprintln!("Skipping {:?}: {}", path, reason);
+    }
+    if graph.unmatched_citations > 0 {
+        eprintln!("{} citation(s) did not match any document in the corpus", graph.unmatched_citations);
+    }
+    eprintln!(
+        "Built citation graph with {} nodes and {} links",
+        graph.nodes.len(),
+        graph.links.len()
+    );
+
+    let output_path = output_dir.join("citation_graph.json");
+    let graph_json = serde_json::to_string_pretty(&graph)?;
+    io::atomic_write(&output_path, graph_json.as_bytes())?;
+
+    eprintln!("Citation graph saved to: {:?}", output_path);
+    Ok(())
+}
+
+fn write_ngrams(
+    processed_path: &Path,
+    n: usize,
+    stride: usize,
+    min_chars: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let blocks = corpus::read_processed_blocks(processed_path)?;
+    let text = ngrams::corpus_text(&blocks, min_chars);
+    let ngrams = ngrams::char_ngrams(&text, n, stride);
+
+    let stem = file_stem(processed_path);
+    let output_path = processed_path.with_file_name(format!("{}_ngrams.jsonl", stem));
+    io::atomic_write(&output_path, ngrams::to_jsonl(&ngrams)?.as_bytes())?;
+
+    eprintln!("{} n-grams saved to: {:?}", ngrams.len(), output_path);
+    Ok(())
+}
+
+fn page_summary(processed_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let blocks = corpus::read_processed_blocks(processed_path)?;
+    let pages = page_summary::summarize_pages(&blocks);
+
+    let stem = file_stem(processed_path);
+    let output_path = processed_path.with_file_name(format!("{}_pages.json", stem));
+    let pages_json = serde_json::to_string_pretty(&pages)?;
+    io::atomic_write(&output_path, pages_json.as_bytes())?;
+
+    eprintln!("Summaries of {} pages saved to: {:?}", pages.len(), output_path);
     Ok(())
 }
 
 fn process_json_file(
     input_path: &Path,
     output_dir: &Option<String>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Processing JSON file: {:?}", input_path);
+    config: &ProcessingConfig,
+) -> Result<stats::ProcessingStats, Box<dyn std::error::Error>> {
+    eprintln!("Processing JSON file: {:?}", input_path);
+    telemetry::trace_file(
+        input_path,
+        &config.output_formats,
+        || {
+            if let Some(count) = config.preview {
+                let processed = load_and_process(input_path, config)?;
+                print_preview(&processed.blocks, &processed.raw_blocks, count, config.preview_raw)?;
+                return Ok(processed.stats(input_path));
+            }
+
+            // Determine output paths, one per requested format
+            let mut output_paths = Vec::new();
+            for &format in &config.output_formats {
+                output_paths.push((format, determine_output_path(input_path, output_dir, format.extension())?));
+            }
+
+            process_and_write(input_path, input_path, &output_paths, config)
+        },
+        |file_stats| file_stats.total_blocks_after,
+    )
+}
+
+// Processes one file of a directory run into output_path. Returns its stats
+// for the run's summary and, if `keep_blocks` is set, the processed file so
+// identical duplicates can be written from it. Files whose blocks aren't
+// kept may be streamed.
+fn process_json_file_with_output_path(
+    input_path: &Path,
+    output_path: &Path,
+    keep_blocks: bool,
+    config: &ProcessingConfig,
+) -> Result<(Option<ProcessedFile>, stats::ProcessingStats), Box<dyn std::error::Error>> {
+    eprintln!("Processing JSON file: {:?}", input_path);
+
+    if let Some(count) = config.preview {
+        let processed = load_and_process(input_path, config)?;
+        print_preview(&processed.blocks, &processed.raw_blocks, count, config.preview_raw)?;
+        let file_stats = processed.stats(input_path);
+        return Ok((Some(processed), file_stats));
+    }
+
+    if keep_blocks {
+        let processed = load_and_process(input_path, config)?;
+        let file_stats = write_outputs_for_path(&processed, input_path, output_path, config)?;
+        return Ok((Some(processed), file_stats));
+    }
+
+    let output_paths = output_paths_for(output_path, config)?;
+    let file_stats = process_and_write(input_path, input_path, &output_paths, config)?;
+    Ok((None, file_stats))
+}
+
+// Writes every output of `processed` for `input_path` to output_path with
+// "_processed" added to the file name, once per requested format
+fn write_outputs_for_path(
+    processed: &ProcessedFile,
+    input_path: &Path,
+    output_path: &Path,
+    config: &ProcessingConfig,
+) -> Result<stats::ProcessingStats, Box<dyn std::error::Error>> {
+    let output_paths = output_paths_for(output_path, config)?;
+    write_file_outputs(processed, input_path, &output_paths, config)
+}
+
+// output_path with "_processed" and each requested format's extension added,
+// with its parent directories created
+fn output_paths_for(
+    output_path: &Path,
+    config: &ProcessingConfig,
+) -> Result<Vec<(OutputFormat, PathBuf)>, Box<dyn std::error::Error>> {
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    Ok(config
+        .output_formats
+        .iter()
+        .map(|&format| (format, processed_output_path(output_path, format.extension())))
+        .collect())
+}
+
+// Processes the Marker JSON at json_path (input_path itself, or Marker's
+// conversion of it) and writes input_path's outputs to output_paths.
+// Streamable files are processed and written a page at a time; the others
+// are read whole first.
+fn process_and_write(
+    input_path: &Path,
+    json_path: &Path,
+    output_paths: &[(OutputFormat, PathBuf)],
+    config: &ProcessingConfig,
+) -> Result<stats::ProcessingStats, Box<dyn std::error::Error>> {
+    if can_stream(json_path, config)
+        && let Some(file_stats) = stream_json_file(input_path, json_path, output_paths, config)?
+    {
+        return Ok(file_stats);
+    }
+
+    let processed = load_and_process(json_path, config)?;
+    write_file_outputs(&processed, input_path, output_paths, config)
+}
+
+// Whether json_path can be streamed: every processing pass works on one
+// page at a time, and no output or side output needs the whole document
+fn can_stream(json_path: &Path, config: &ProcessingConfig) -> bool {
+    json_path.extension().and_then(|ext| ext.to_str()) != Some("toml")
+        && config.processes_pages_independently()
+        && config.output_formats.iter().all(|&format| streaming::supports(format))
+        && config.preview.is_none()
+        && !config.generate_report
+        && !config.extract_tables
+        && config.validate_charset.is_none()
+        && !config.split_at_boundaries
+        && !config.content_hash
+        && !config.group_by_section
+        && !config.extract_index
+        && config.sliding_window_summary.is_none()
+}
+
+// Processes a Marker JSON file one page at a time, writing each page's
+// blocks to output_paths before the next page is read, so the document is
+// never in memory whole. Returns None, having written nothing, if the file
+// isn't Marker JSON that parses as it is read: block lists written by this
+// tool, input that isn't UTF-8, or invalid JSON. load_and_process then reads
+// it whole, with its encoding fallback and error messages.
+fn stream_json_file(
+    input_path: &Path,
+    json_path: &Path,
+    output_paths: &[(OutputFormat, PathBuf)],
+    config: &ProcessingConfig,
+) -> Result<Option<stats::ProcessingStats>, Box<dyn std::error::Error>> {
+    let started = Instant::now();
+    let file = fs::File::open(json_path)?;
+
+    let mut writers = Vec::new();
+    for &(format, ref output_path) in output_paths {
+        let output = io::AtomicFile::create(output_path)?;
+        writers.push(streaming::BlockWriter::new(format, output).expect("checked by can_stream"));
+    }
+
+    // The counts process_document would report for the whole document
+    let mut totals = ProcessedDocument::default();
+    let mut blocks_before = BTreeMap::new();
+
+    let result = pdf_parser::reader::read_pages(BufReader::new(file), false, |mut page| {
+        add_counts(&mut blocks_before, stats::count_blocks_by_type(std::slice::from_ref(&page)));
+        if config.base64_decode {
+            base64::decode_blocks(std::slice::from_mut(&mut page), &config.base64_fields)?;
+        }
+
+        let mut processed = process_document(Document { children: vec![page] }, config);
+        if config.base64_encode {
+            base64::encode_blocks(&mut processed.blocks, &config.base64_fields);
+        }
+        for writer in &mut writers {
+            for block in &processed.blocks {
+                writer.write_block(block)?;
+            }
+        }
+        add_page_totals(&mut totals, processed, config);
+        Ok::<(), Box<dyn std::error::Error>>(())
+    });
+    match result {
+        // Dropping the writers discards what was written
+        Err(e) if e.downcast_ref::<serde_json::Error>().is_some_and(|e| !e.is_io()) => return Ok(None),
+        Err(e) => return Err(e),
+        Ok(()) => {}
+    }
+    report_processing(&totals, config);
+
+    for writer in writers {
+        let output = writer.finish()?;
+        let output_path = output.path().to_path_buf();
+        output.commit()?;
+        eprintln!("Processed output saved to: {:?}", output_path);
+    }
+
+    let file_stats = stats::ProcessingStats::from_counts(
+        &input_path.display().to_string(),
+        blocks_before,
+        totals.block_type_histogram,
+        started.elapsed(),
+    );
+    if config.stats {
+        write_stats(&file_stats, input_path, output_paths)?;
+    }
+    Ok(Some(file_stats))
+}
+
+fn add_counts(totals: &mut BTreeMap<String, usize>, counts: BTreeMap<String, usize>) {
+    for (block_type, count) in counts {
+        *totals.entry(block_type).or_insert(0) += count;
+    }
+}
+
+// Adds the counts and findings of one page's process_document run to the
+// document's. The page's blocks have already been written out.
+fn add_page_totals(totals: &mut ProcessedDocument, page: ProcessedDocument, config: &ProcessingConfig) {
+    // Flags index the blocks as they were before flagged ones were dropped
+    let mut page_offset = totals.output_block_count;
+    if config.drop_punctuation_noise {
+        page_offset += totals.punctuation_flags.len();
+    }
+    totals.input_block_count += page.input_block_count;
+    totals.output_block_count += page.output_block_count;
+    add_counts(&mut totals.block_type_histogram, page.block_type_histogram);
+    totals.small_pictures_dropped += page.small_pictures_dropped;
+    totals.punctuation_flags.extend(page.punctuation_flags.into_iter().map(|mut flag| {
+        flag.index += page_offset;
+        flag
+    }));
+}
+
+// An input file after processing, with what its side outputs need
+struct ProcessedFile {
+    // The Marker blocks, kept only if the preview or report needs them
+    raw_blocks: Vec<Block>,
+    // Block counts over the whole tree before processing, for --stats
+    blocks_before: BTreeMap<String, usize>,
+    blocks: Vec<Block>,
+    // Time spent reading and processing the file
+    elapsed: Duration,
+}
+
+impl ProcessedFile {
+    fn stats(&self, input_path: &Path) -> stats::ProcessingStats {
+        stats::ProcessingStats::new(
+            &input_path.display().to_string(),
+            self.blocks_before.clone(),
+            &self.blocks,
+            self.elapsed,
+        )
+    }
+}
+
+fn load_and_process(input_path: &Path, config: &ProcessingConfig) -> Result<ProcessedFile, Box<dyn std::error::Error>> {
+    let started = Instant::now();
+
+    let (mut input_blocks, already_processed) = match read_document_file(input_path, config)? {
+        InputDocument::Marker(document) => (document.children, false),
+        InputDocument::Processed(blocks) => (blocks, true),
+    };
+    if config.base64_decode {
+        base64::decode_blocks(&mut input_blocks, &config.base64_fields)?;
+    }
+
+    // Keep the raw blocks around only if they are needed for the preview or report
+    let raw_blocks = if config.preview_raw || config.generate_report {
+        input_blocks.clone()
+    } else {
+        Vec::new()
+    };
+
+    // Counted over the whole tree before processing consumes it
+    let blocks_before = stats::count_blocks_by_type(&input_blocks);
+
+    // Process the document to remove non-content elements. Blocks that were
+    // already processed are not flattened again, so they keep any edits to
+    // their text, but the enabled passes still run on them.
+    let processed = if already_processed {
+        reprocess_blocks(input_blocks, config)
+    } else {
+        process_document(Document { children: input_blocks }, config)
+    };
+    report_processing(&processed, config);
+    let blocks = processed.blocks;
+
+    Ok(ProcessedFile {
+        raw_blocks,
+        blocks_before,
+        blocks,
+        elapsed: started.elapsed(),
+    })
+}
+
+// Prints what the enabled processing passes found to stderr
+fn report_processing(processed: &ProcessedDocument, config: &ProcessingConfig) {
+    if processed.small_pictures_dropped > 0 {
+        eprintln!("Dropped {} small picture(s)", processed.small_pictures_dropped);
+    }
+    if config.detect_doc_boundaries {
+        eprintln!("Found {} document boundaries", processed.document_boundaries);
+    }
+    if processed.tables_merged > 0 {
+        eprintln!("Merged {} split table(s)", processed.tables_merged);
+    }
+    if config.expand_abbreviations {
+        eprintln!(
+            "Expanded {} use(s) of {} defined abbreviation(s)",
+            processed.abbreviations_expanded,
+            processed.abbreviations.len()
+        );
+    }
+
+    let flags = &processed.punctuation_flags;
+    if !flags.is_empty() {
+        eprintln!("Blocks with punctuation noise:");
+        for flag in flags {
+            match flag.run {
+                Some((c, count)) => eprintln!(
+                    "  {}: ratio {:.2}, '{}' repeated {} times",
+                    flag.id, flag.ratio, c, count
+                ),
+                None => eprintln!("  {}: ratio {:.2}", flag.id, flag.ratio),
+            }
+        }
+    }
+    if config.drop_punctuation_noise {
+        eprintln!("Dropped {} noisy block(s)", flags.len());
+    }
+}
+
+// Writes the processed outputs and every requested side output (report,
+// stats, ...) of `processed` as the outputs of `input_path`
+fn write_file_outputs(
+    processed: &ProcessedFile,
+    input_path: &Path,
+    output_paths: &[(OutputFormat, PathBuf)],
+    config: &ProcessingConfig,
+) -> Result<stats::ProcessingStats, Box<dyn std::error::Error>> {
+    write_processed_outputs(&processed.blocks, input_path, output_paths, config)?;
+
+    if config.generate_report {
+        write_report(processed, input_path, output_paths, config)?;
+    }
+
+    let file_stats = processed.stats(input_path);
+    if config.stats {
+        write_stats(&file_stats, input_path, output_paths)?;
+    }
+    Ok(file_stats)
+}
+
+// An input file: a Marker document still to be processed, or blocks that
+// were already processed, as written by --output-format json or toml
+enum InputDocument {
+    Marker(Document),
+    Processed(Vec<Block>),
+}
+
+// Reads an input file. Marker JSON is parsed straight from the file; if that
+// fails the file is read whole and parsed again, which decodes non-UTF-8
+// input and gives the error messages of parse_document. Image data is only
+// kept when the report or raw preview shows it.
+fn read_document_file(
+    input_path: &Path,
+    config: &ProcessingConfig,
+) -> Result<InputDocument, Box<dyn std::error::Error>> {
+    if input_path.extension().and_then(|ext| ext.to_str()) != Some("toml") {
+        let file = fs::File::open(input_path)?;
+        let keep_images = config.generate_report || config.preview_raw;
+        match pdf_parser::reader::read_document(BufReader::new(file), keep_images) {
+            Ok(document) => return Ok(InputDocument::Marker(document)),
+            Err(e) if e.is_io() => return Err(e.into()),
+            Err(_) => {}
+        }
+    }
+
+    let content = read_input_file(input_path, config)?;
+    parse_document(input_path, &content)
+}
+
+// Parses an input file. TOML input and JSON with a top-level array are
+// block lists as written by --output-format toml or json; anything else is
+// treated as Marker JSON.
+fn parse_document(input_path: &Path, content: &str) -> Result<InputDocument, Box<dyn std::error::Error>> {
+    if input_path.extension().and_then(|ext| ext.to_str()) == Some("toml") {
+        return match toml_format::blocks_from_toml(content) {
+            Ok(blocks) => Ok(InputDocument::Processed(blocks)),
+            Err(e) => Err(format!("Invalid TOML in {:?}: {}", input_path, e).into()),
+        };
+    }
+
+    if content.trim_start().starts_with('[') {
+        return match serde_json::from_str(content) {
+            Ok(blocks) => Ok(InputDocument::Processed(blocks)),
+            Err(e) => Err(format!("Invalid processed JSON in {:?}: {}", input_path, e).into()),
+        };
+    }
 
-    // Read the JSON file
-    let json_content = fs::read_to_string(input_path)?;
-    
     // Try to parse as Document, if it fails, it's likely not a valid Marker JSON
-    let document: Document = match serde_json::from_str(&json_content) {
-        Ok(doc) => doc,
+    match serde_json::from_str(content) {
+        Ok(doc) => Ok(InputDocument::Marker(doc)),
+        Err(e) => Err(format!("Invalid JSON schema in {:?}: {}", input_path, e).into()),
+    }
+}
+
+// Reads an input file as UTF-8, falling back to Latin-1 or Windows-1252 for
+// files written by tools that don't emit UTF-8 (unless --strict-utf8 is set)
+fn read_input_file(
+    input_path: &Path,
+    config: &ProcessingConfig,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let bytes = fs::read(input_path)?;
+    match String::from_utf8(bytes) {
+        Ok(content) => Ok(content),
+        Err(e) if config.strict_utf8 => Err(format!("{:?} is not valid UTF-8: {}", input_path, e).into()),
         Err(e) => {
-            return Err(format!("Invalid JSON schema in {:?}: {}", input_path, e).into());
+            let (content, detected) = encoding::decode_invalid_runs(e.as_bytes());
+            eprintln!(
+                "{:?} is not valid UTF-8, decoding the invalid bytes as {}",
+                input_path,
+                detected.label()
+            );
+            Ok(content)
         }
+    }
+}
+
+// Prints the first `count` processed blocks as a JSON array instead of
+// writing any output. With `include_raw`, each entry pairs the processed
+// block with the Marker block it came from.
+fn print_preview(
+    blocks: &[Block],
+    raw_blocks: &[Block],
+    count: usize,
+    include_raw: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let preview = &blocks[..count.min(blocks.len())];
+
+    let preview_json = if include_raw {
+        #[derive(Serialize)]
+        struct PreviewEntry<'a> {
+            raw: Option<&'a Block>,
+            processed: &'a Block,
+        }
+
+        let entries: Vec<PreviewEntry> = preview
+            .iter()
+            .map(|block| PreviewEntry {
+                raw: find_block_by_id(raw_blocks, &block.id),
+                processed: block,
+            })
+            .collect();
+        serde_json::to_string_pretty(&entries)?
+    } else {
+        serde_json::to_string_pretty(preview)?
     };
 
-    // Process the document to remove non-content elements
-    let filtered_blocks = flatten_and_filter_blocks(document.children);
+    println!("{}", preview_json);
+    Ok(())
+}
 
-    // Determine output path
-    let output_path = determine_output_path(input_path, output_dir, "json")?;
-    
-    // Write the processed JSON to file
-    let mut output_file = File::create(&output_path)?;
-    let processed_json = serde_json::to_string_pretty(&filtered_blocks)?;
-    output_file.write_all(processed_json.as_bytes())?;
+fn find_block_by_id<'a>(blocks: &'a [Block], id: &str) -> Option<&'a Block> {
+    for block in blocks {
+        if block.id == id {
+            return Some(block);
+        }
+        if let Some(found) = block
+            .children
+            .as_deref()
+            .and_then(|children| find_block_by_id(children, id))
+        {
+            return Some(found);
+        }
+    }
+    None
+}
+
+// Serializes the filtered blocks once per requested format. Blocks are only
+// processed once, whatever the number of formats.
+fn write_processed_outputs(
+    blocks: &[Block],
+    input_path: &Path,
+    output_paths: &[(OutputFormat, PathBuf)],
+    config: &ProcessingConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let extracted;
+    let blocks = match output_paths.first() {
+        Some((_, output_path)) if config.extract_tables => {
+            extracted = extract_tables(blocks, input_path, output_path)?;
+            &extracted[..]
+        }
+        _ => blocks,
+    };
+
+    if let Some(target) = config.validate_charset {
+        report_charset_violations(blocks, target);
+    }
+
+    // Documents are split before base64 encoding, which would hide the
+    // boundary blocks' type. Side outputs below are computed from the plain text.
+    let mut documents: Vec<Cow<[Block]>> = if config.split_at_boundaries {
+        boundaries::split_at_boundaries(blocks)
+            .into_iter()
+            .map(Cow::Owned)
+            .collect()
+    } else {
+        vec![Cow::Borrowed(blocks)]
+    };
+    if config.base64_encode {
+        for document in &mut documents {
+            base64::encode_blocks(document.to_mut(), &config.base64_fields);
+        }
+    }
+
+    for (format, output_path) in output_paths {
+        if config.split_at_boundaries {
+            for (index, document) in documents.iter().enumerate() {
+                let document_path = document_output_path(output_path, index + 1);
+                let processed_output = render_output(document, input_path, *format, config)?;
+                io::atomic_write(&document_path, &processed_output)?;
+
+                eprintln!("Document {} of {} saved to: {:?}", index + 1, documents.len(), document_path);
+            }
+        } else {
+            let processed_output = render_output(&documents[0], input_path, *format, config)?;
+            io::atomic_write(output_path, &processed_output)?;
+
+            eprintln!("Processed output saved to: {:?}", output_path);
+        }
+    }
+
+    // Side outputs are written next to the first processed output
+    if let Some((_, output_path)) = output_paths.first() {
+        if config.content_hash {
+            write_content_hash(blocks, input_path, output_path)?;
+        }
 
-    println!("Processed JSON saved to: {:?}", output_path);
+        if config.group_by_section {
+            let groups = sections::group_blocks_by_section(blocks.to_vec());
+            let grouped_path = sidecar_path(input_path, output_path, "_grouped.json");
+            let grouped_json = serde_json::to_string_pretty(&groups)?;
+            io::atomic_write(&grouped_path, grouped_json.as_bytes())?;
+            eprintln!("Grouped sections saved to: {:?}", grouped_path);
+        }
+
+        if config.extract_index {
+            let entries = index_terms::extract_index_entries(blocks);
+            let index_path = sidecar_path(input_path, output_path, "_index.json");
+            let index_json = serde_json::to_string_pretty(&entries)?;
+            io::atomic_write(&index_path, index_json.as_bytes())?;
+            eprintln!("{} index entries saved to: {:?}", entries.len(), index_path);
+        }
+
+        if let Some(window) = &config.sliding_window_summary {
+            let summary = summarize::sliding_window_summary(blocks, window);
+            let summary_path = sidecar_path(input_path, output_path, "_summary.txt");
+            io::atomic_write(&summary_path, (summary.join("\n") + "\n").as_bytes())?;
+            eprintln!("Summary saved to: {:?}", summary_path);
+        }
+    }
     Ok(())
 }
 
-fn process_json_file_with_output_path(
+// Writes each Table block to <stem>_table_<n>.csv next to the first
+// processed output, numbering tables in document order from 0. Returns the
+// blocks with each extracted table's text rendered as tab-separated rows and
+// table_csv_path set to the CSV's file name. Tables whose HTML can't be
+// parsed are reported and left unchanged.
+fn extract_tables(
+    blocks: &[Block],
     input_path: &Path,
     output_path: &Path,
+) -> Result<Vec<Block>, Box<dyn std::error::Error>> {
+    let mut blocks = blocks.to_vec();
+    let tables = blocks.iter_mut().filter(|block| block.block_type == "Table");
+    for (index, block) in tables.enumerate() {
+        let rows = match tables::parse_html_table(&block.html) {
+            Ok(rows) => rows,
+            Err(e) => {
+                eprintln!("Could not extract table {} ({}): {}", index, block.id, e);
+                continue;
+            }
+        };
+
+        let csv_path = sidecar_path(input_path, output_path, &format!("_table_{}.csv", index));
+        io::atomic_write(&csv_path, tables::render_csv(&rows).as_bytes())?;
+        eprintln!("Table {} saved to: {:?}", index, csv_path);
+
+        block.text = tables::render_tab_separated(&rows);
+        block.table_csv_path = csv_path.file_name().map(|name| name.to_string_lossy().into_owned());
+    }
+    Ok(blocks)
+}
+
+fn report_charset_violations(blocks: &[Block], target: encoding::Encoding) {
+    let violations = encoding::validate_charset(blocks, target);
+    if violations.is_empty() {
+        return;
+    }
+
+    eprintln!("Blocks with characters not representable in {}:", target.label());
+    for violation in &violations {
+        let chars: Vec<String> = violation
+            .chars
+            .iter()
+            .map(|&c| format!("{:?} (U+{:04X})", c, c as u32))
+            .collect();
+        eprintln!("  {}: {}", violation.id, chars.join(", "));
+    }
+}
+
+// Writes <stem>_stats.json next to the first processed output
+fn write_stats(
+    file_stats: &stats::ProcessingStats,
+    input_path: &Path,
+    output_paths: &[(OutputFormat, PathBuf)],
 ) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Processing JSON file: {:?}", input_path);
+    let Some((_, first_output)) = output_paths.first() else {
+        return Ok(());
+    };
+    let stats_path = sidecar_path(input_path, first_output, "_stats.json");
+    let stats_json = serde_json::to_string_pretty(file_stats)?;
+    io::atomic_write(&stats_path, stats_json.as_bytes())?;
+    eprintln!("Stats saved to: {:?}", stats_path);
+    Ok(())
+}
 
-    // Read the JSON file
-    let json_content = fs::read_to_string(input_path)?;
-    
-    // Try to parse as Document, if it fails, it's likely not a valid Marker JSON
-    let document: Document = match serde_json::from_str(&json_content) {
-        Ok(doc) => doc,
-        Err(e) => {
-            return Err(format!("Invalid JSON schema in {:?}: {}", input_path, e).into());
-        }
+// Writes <stem>_report.html next to the first processed output
+fn write_report(
+    processed: &ProcessedFile,
+    input_path: &Path,
+    output_paths: &[(OutputFormat, PathBuf)],
+    config: &ProcessingConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Some((_, first_output)) = output_paths.first() else {
+        return Ok(());
+    };
+
+    let run = report::RunSummary {
+        input_path,
+        input_size: fs::metadata(input_path)?.len(),
+        // Outputs split at document boundaries are not listed
+        output_files: output_paths
+            .iter()
+            .filter_map(|(_, path)| Some((path.clone(), fs::metadata(path).ok()?.len())))
+            .collect(),
+        elapsed: processed.elapsed,
     };
+    let html = report::render_report(&run, &processed.raw_blocks, &processed.blocks, config);
 
-    // Process the document to remove non-content elements
-    let filtered_blocks = flatten_and_filter_blocks(document.children);
+    let report_path = sidecar_path(input_path, first_output, "_report.html");
+    io::atomic_write(&report_path, html.as_bytes())?;
+    eprintln!("Report saved to: {:?}", report_path);
+    Ok(())
+}
+
+// Path for a side output named after the input file (e.g. <stem>.sha256),
+// placed in the same directory as the processed output
+fn sidecar_path(input_path: &Path, output_path: &Path, suffix: &str) -> PathBuf {
+    output_path.with_file_name(format!("{}{}", file_stem(input_path), suffix))
+}
 
-    // Modify the output path to add "_processed" to the filename
-    let file_name = output_path
-        .file_stem()
+// File name of `path` without its extension, or "output" if it has none
+// that is valid UTF-8
+fn file_stem(path: &Path) -> &str {
+    path.file_stem()
         .and_then(|name| name.to_str())
-        .unwrap_or("output");
-    let output_file_name = format!("{}_processed.json", file_name);
-    
-    let final_output_path = if let Some(parent) = output_path.parent() {
+        .unwrap_or("output")
+}
+
+// Prints the content fingerprint and saves it as <input stem>.sha256 in the
+// directory the processed output was written to
+fn write_content_hash(
+    blocks: &[Block],
+    input_path: &Path,
+    output_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let hash = fingerprint::document_content_hash(blocks);
+    println!("{}  {}", hash, input_path.display());
+
+    let hash_path = sidecar_path(input_path, output_path, ".sha256");
+    io::atomic_write(&hash_path, format!("{}\n", hash).as_bytes())?;
+    Ok(())
+}
+
+// <stem>_processed.<ext> -> <stem>_doc<N>_processed.<ext>
+fn document_output_path(output_path: &Path, number: usize) -> PathBuf {
+    let stem = file_stem(output_path);
+    let base = stem.strip_suffix("_processed").unwrap_or(stem);
+    let file_name = match output_path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => format!("{}_doc{}_processed.{}", base, number, ext),
+        None => format!("{}_doc{}_processed", base, number),
+    };
+    output_path.with_file_name(file_name)
+}
+
+fn processed_output_path(output_path: &Path, extension: &str) -> PathBuf {
+    let file_name = file_stem(output_path);
+    let output_file_name = format!("{}_processed.{}", file_name, extension);
+
+    if let Some(parent) = output_path.parent() {
         parent.join(output_file_name)
     } else {
         PathBuf::from(output_file_name)
-    };
-    
-    // Create parent directories if they don't exist
-    if let Some(parent) = final_output_path.parent() {
-        fs::create_dir_all(parent)?;
     }
-    
-    // Write the processed JSON to file
-    let mut output_file = File::create(&final_output_path)?;
-    let processed_json = serde_json::to_string_pretty(&filtered_blocks)?;
-    output_file.write_all(processed_json.as_bytes())?;
+}
 
-    println!("Processed JSON saved to: {:?}", final_output_path);
-    Ok(())
+fn render_output(
+    blocks: &[Block],
+    input_path: &Path,
+    format: OutputFormat,
+    config: &ProcessingConfig,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let output = match format {
+        OutputFormat::Json => serde_json::to_string_pretty(blocks)?,
+        OutputFormat::Toml => toml_format::blocks_to_toml(blocks)?,
+        OutputFormat::Text => {
+            let texts: Vec<&str> = blocks.iter().map(|block| block.text.as_str()).collect();
+            texts.join("\n") + "\n"
+        }
+        OutputFormat::Markdown => {
+            let mut output = String::new();
+            if config.yaml_front_matter {
+                let modified = fs::metadata(input_path)?.modified()?;
+                output.push_str(&front_matter::render_front_matter(blocks, input_path, modified)?);
+            }
+            output.push_str(&markdown::render_markdown(blocks, config.auto_table_format));
+            output
+        }
+        OutputFormat::Parquet => {
+            let source_file = input_path.display().to_string();
+            return Ok(parquet_format::blocks_to_parquet(blocks, &source_file)?);
+        }
+    };
+    Ok(output.into_bytes())
 }
 
 fn process_pdf_file(
     input_path: &Path,
-    _output_dir: &Option<String>,
+    output_dir: &Option<String>,
+    config: &ProcessingConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Processing PDF file: {:?}", input_path);
-    
-    // For now, we'll just print a message since the actual PDF processing 
-    // would require calling the Python marker tool
-    println!("PDF processing would call marker tool here");
-    
-    // In a full implementation, we would:
-    // 1. Call the marker tool to convert PDF to JSON
-    // 2. Process the resulting JSON as in process_json_file
-    
-    Ok(())
+    // Same naming as JSON input: <output dir or PDF dir>/<stem>_processed.<ext>
+    let output_path = match output_dir {
+        Some(dir) => Path::new(dir).join(input_path.file_name().unwrap_or_default()),
+        None => input_path.to_path_buf(),
+    };
+    process_pdf_file_with_output_path(input_path, &output_path, config).map(|_| ())
 }
 
+// Converts the PDF with Marker and processes the resulting JSON into
+// output_path. Returns the file's stats and the path of the kept Marker
+// JSON, if --keep-intermediate is set.
 fn process_pdf_file_with_output_path(
     input_path: &Path,
     output_path: &Path,
-) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Processing PDF file: {:?}", input_path);
-    
-    // For now, we'll just print a message since the actual PDF processing 
-    // would require calling the Python marker tool
-    println!("PDF processing would call marker tool here and save to: {:?}", output_path);
-    
-    // In a full implementation, we would:
-    // 1. Call the marker tool to convert PDF to JSON
-    // 2. Process the resulting JSON and save it to output_path
-    
-    Ok(())
+    config: &ProcessingConfig,
+) -> Result<(stats::ProcessingStats, Option<PathBuf>), Box<dyn std::error::Error>> {
+    eprintln!("Processing PDF file: {:?}", input_path);
+    telemetry::trace_file(
+        input_path,
+        &config.output_formats,
+        || {
+            let work_dir = marker::work_dir()?;
+            let json_path = marker::convert_pdf(&config.marker_bin, input_path, work_dir.path())?;
+            process_marker_output(input_path, &json_path, output_path, config)
+        },
+        |(file_stats, _)| file_stats.total_blocks_after,
+    )
+}
+
+// Processes the JSON Marker wrote for the PDF at input_path as that PDF's
+// content, so outputs and sidecars are named after the PDF. With
+// --keep-intermediate the JSON is first copied next to the processed output.
+fn process_marker_output(
+    input_path: &Path,
+    json_path: &Path,
+    output_path: &Path,
+    config: &ProcessingConfig,
+) -> Result<(stats::ProcessingStats, Option<PathBuf>), Box<dyn std::error::Error>> {
+    if let Some(count) = config.preview {
+        let processed = load_and_process(json_path, config)?;
+        print_preview(&processed.blocks, &processed.raw_blocks, count, config.preview_raw)?;
+        return Ok((processed.stats(input_path), None));
+    }
+
+    let output_paths = output_paths_for(output_path, config)?;
+    let file_stats = process_and_write(input_path, json_path, &output_paths, config)?;
+
+    // Kept next to the outputs, whose directory now exists
+    let kept_path = if config.keep_intermediate {
+        let kept_path = sidecar_path(input_path, output_path, ".json");
+        fs::copy(json_path, &kept_path)?;
+        eprintln!("Marker output kept at: {:?}", kept_path);
+        Some(kept_path)
+    } else {
+        None
+    };
+
+    Ok((file_stats, kept_path))
+}
+
+// Groups files with byte-identical content, keeping the order in which each
+// group's first file was found. Files are bucketed by an FNV hash of their
+// content and compared byte for byte on a hash match, so a collision never
+// merges different files. Unreadable files are reported and left out.
+fn group_identical_files(
+    paths: Vec<PathBuf>,
+    unprocessed_files: &mut Vec<UnprocessedFile>,
+) -> Vec<Vec<PathBuf>> {
+    let mut groups: Vec<Vec<PathBuf>> = Vec::new();
+    // Content hash -> indices of the groups whose files have that hash
+    let mut groups_by_hash: HashMap<u64, Vec<usize>> = HashMap::new();
+
+    for path in paths {
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                unprocessed_files.push(UnprocessedFile {
+                    path: path.to_string_lossy().to_string(),
+                    reason: format!("Error reading file: {}", e),
+                });
+                continue;
+            }
+        };
+
+        let candidates = groups_by_hash.entry(dedup::fnv1a_hash(&bytes)).or_default();
+        let identical = candidates
+            .iter()
+            .copied()
+            .find(|&index| fs::read(&groups[index][0]).is_ok_and(|original| original == bytes));
+        match identical {
+            Some(index) => groups[index].push(path),
+            None => {
+                candidates.push(groups.len());
+                groups.push(vec![path]);
+            }
+        }
+    }
+    groups
 }
 
 fn process_pdf_directory_with_structure(
     input_dir: &Path,
     output_dir: &str,
+    config: &ProcessingConfig,
 ) -> Result<Vec<UnprocessedFile>, Box<dyn std::error::Error>> {
-    println!("Processing directory with structure: {:?}", input_dir);
+    eprintln!("Processing directory with structure: {:?}", input_dir);
     
     let mut unprocessed_files = Vec::new();
+    let mut summary = SummaryStats::default();
+
+    // Marker JSON kept by --keep-intermediate for the PDFs below, which was
+    // processed along with its PDF
+    let mut marker_outputs: HashSet<PathBuf> = HashSet::new();
     
     // Convert input_dir to a canonical path for consistent comparison
     let canonical_input_dir = input_dir.canonicalize()?;
@@ -277,74 +1474,140 @@ fn process_pdf_directory_with_structure(
     };
     
     // Find all PDF files in the directory and subdirectories (excluding target and .git)
+    let mut pdf_files = Vec::new();
     let pdf_pattern = format!("{}/**/*.pdf", canonical_input_dir.display());
     for entry in glob(&pdf_pattern)? {
         match entry {
             Ok(path) => {
                 // Skip files in target and .git directories
-                if is_excluded_path(&path) {
-                    continue;
+                if !is_excluded_path(&path) {
+                    pdf_files.push(path);
                 }
-                
-                // Determine the relative path from input_dir to this file
-                if let Ok(relative_path) = path.strip_prefix(&canonical_input_dir) {
-                    // Create the corresponding output path
-                    let output_path = Path::new(output_dir).join(relative_path);
-                    
-                    // Create the parent directories if they don't exist
-                    if let Some(parent) = output_path.parent() {
-                        fs::create_dir_all(parent)?;
+            }
+            Err(e) => {
+                unprocessed_files.push(UnprocessedFile {
+                    path: "Unknown file".to_string(),
+                    reason: format!("Error reading file: {:?}", e),
+                });
+            }
+        }
+    }
+
+    // Identical PDFs are only converted once; the outputs of the others are
+    // written from the first one's Marker output under their own names
+    let pdf_groups = if config.dedup {
+        group_identical_files(pdf_files, &mut unprocessed_files)
+    } else {
+        pdf_files.into_iter().map(|path| vec![path]).collect()
+    };
+    let mut duplicates_of: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+    let mut originals = Vec::new();
+    for mut group in pdf_groups {
+        let duplicates = group.split_off(1);
+        duplicates_of.insert(group[0].clone(), duplicates);
+        originals.extend(group);
+    }
+
+    // Marker converts the PDFs in as few batch runs as their file names allow
+    for batch in marker::batches(originals) {
+        let converted = marker::work_dir().and_then(|work_dir| {
+            marker::convert_pdf_batch(&config.marker_batch_bin, &batch, work_dir.path())
+                .map(|json_paths| (work_dir, json_paths))
+        });
+        let (_work_dir, json_paths) = match converted {
+            Ok(converted) => converted,
+            Err(e) => {
+                for path in batch.iter().flat_map(|path| std::iter::once(path).chain(&duplicates_of[path])) {
+                    unprocessed_files.push(UnprocessedFile {
+                        path: path.to_string_lossy().to_string(),
+                        reason: format!("Error processing PDF: {}", e),
+                    });
+                }
+                continue;
+            }
+        };
+
+        for (path, json_path) in batch.iter().zip(json_paths) {
+            let duplicates = &duplicates_of[path];
+
+            // Determine the relative path from input_dir to each file, and
+            // the corresponding output path. Its parent directories are
+            // created when the outputs are written, so --preview leaves the
+            // output tree untouched.
+            let output_path_of = |path: &Path| {
+                path.strip_prefix(&canonical_input_dir)
+                    .map(|relative_path| Path::new(output_dir).join(relative_path))
+            };
+            let Ok(output_path) = output_path_of(path) else {
+                continue;
+            };
+
+            eprintln!("Processing PDF file: {:?}", path);
+            let result = telemetry::trace_file(
+                path,
+                &config.output_formats,
+                || {
+                    let json_path = json_path?;
+                    let processed = process_marker_output(path, &json_path, &output_path, config)?;
+                    Ok((processed, json_path))
+                },
+                |((file_stats, _), _)| file_stats.total_blocks_after,
+            );
+            let json_path = match result {
+                Ok(((file_stats, kept_path), json_path)) => {
+                    summary.add(&file_stats);
+                    if let Some(kept_path) = kept_path {
+                        marker_outputs.insert(kept_path.canonicalize().unwrap_or(kept_path));
                     }
-                    
-                    // Process the PDF file with the output path
-                    if let Err(e) = process_pdf_file_with_output_path(&path, &output_path) {
+                    json_path
+                }
+                Err(e) => {
+                    for path in std::iter::once(path).chain(duplicates) {
                         unprocessed_files.push(UnprocessedFile {
                             path: path.to_string_lossy().to_string(),
                             reason: format!("Error processing PDF: {}", e),
                         });
                     }
+                    continue;
+                }
+            };
+
+            for duplicate in duplicates {
+                let Ok(duplicate_output_path) = output_path_of(duplicate) else {
+                    continue;
+                };
+                eprintln!("Skipped duplicate of: {}", path.display());
+                summary.files_skipped_duplicate += 1;
+                match process_marker_output(duplicate, &json_path, &duplicate_output_path, config) {
+                    Ok((_, Some(kept_path))) => {
+                        marker_outputs.insert(kept_path.canonicalize().unwrap_or(kept_path));
+                    }
+                    Ok((_, None)) => {}
+                    Err(e) => unprocessed_files.push(UnprocessedFile {
+                        path: duplicate.to_string_lossy().to_string(),
+                        reason: format!("Error writing duplicate output: {}", e),
+                    }),
                 }
-            }
-            Err(e) => {
-                unprocessed_files.push(UnprocessedFile {
-                    path: "Unknown file".to_string(),
-                    reason: format!("Error reading file: {:?}", e),
-                });
             }
         }
     }
-    
+
     // Also check for JSON files in the directory and subdirectories (excluding target and .git)
+    let mut json_files = Vec::new();
     let json_pattern = format!("{}/**/*.json", canonical_input_dir.display());
     for entry in glob(&json_pattern)? {
         match entry {
             Ok(path) => {
-                // Skip files in target and .git directories
-                if is_excluded_path(&path) {
+                // Skip files in target and .git directories, Marker output
+                // already processed with its PDF, and already processed files
+                // (those with "_processed" in the name)
+                if is_excluded_path(&path)
+                    || marker_outputs.contains(&path)
+                    || path.to_string_lossy().contains("_processed")
+                {
                     continue;
                 }
-                
-                // Skip already processed files (those with "_processed" in the name)
-                if !path.to_string_lossy().contains("_processed") {
-                    // Determine the relative path from input_dir to this file
-                    if let Ok(relative_path) = path.strip_prefix(&canonical_input_dir) {
-                        // Create the corresponding output path
-                        let output_path = Path::new(output_dir).join(relative_path);
-                        
-                        // Create the parent directories if they don't exist
-                        if let Some(parent) = output_path.parent() {
-                            fs::create_dir_all(parent)?;
-                        }
-                        
-                        // Process the JSON file with the output path
-                        if let Err(e) = process_json_file_with_output_path(&path, &output_path) {
-                            unprocessed_files.push(UnprocessedFile {
-                                path: path.to_string_lossy().to_string(),
-                                reason: format!("{}", e),
-                            });
-                        }
-                    }
-                }
+                json_files.push(path);
             }
             Err(e) => {
                 unprocessed_files.push(UnprocessedFile {
@@ -354,7 +1617,69 @@ fn process_pdf_directory_with_structure(
             }
         }
     }
-    
+
+    // Identical documents are only processed once; the outputs of the others
+    // are written from the first one's processed blocks
+    let groups = if config.dedup {
+        group_identical_files(json_files, &mut unprocessed_files)
+    } else {
+        json_files.into_iter().map(|path| vec![path]).collect()
+    };
+
+    for group in groups {
+        let (original, duplicates) = group.split_first().expect("groups are never empty");
+
+        // Determine the relative path from input_dir to each file, and the
+        // corresponding output path
+        let output_path_of = |path: &Path| {
+            path.strip_prefix(&canonical_input_dir)
+                .map(|relative_path| Path::new(output_dir).join(relative_path))
+        };
+        let Ok(output_path) = output_path_of(original) else {
+            continue;
+        };
+
+        // Process the JSON file with the output path
+        let processed = match telemetry::trace_file(
+            original,
+            &config.output_formats,
+            || process_json_file_with_output_path(original, &output_path, !duplicates.is_empty(), config),
+            |(_, file_stats)| file_stats.total_blocks_after,
+        ) {
+            Ok((processed, file_stats)) => {
+                summary.add(&file_stats);
+                processed
+            }
+            Err(e) => {
+                for path in &group {
+                    unprocessed_files.push(UnprocessedFile {
+                        path: path.to_string_lossy().to_string(),
+                        reason: format!("{}", e),
+                    });
+                }
+                continue;
+            }
+        };
+
+        // The processed blocks are kept whenever there are duplicates
+        let Some(processed) = processed else {
+            continue;
+        };
+        for duplicate in duplicates {
+            let Ok(duplicate_output_path) = output_path_of(duplicate) else {
+                continue;
+            };
+            eprintln!("Skipped duplicate of: {}", original.display());
+            summary.files_skipped_duplicate += 1;
+            if let Err(e) = write_outputs_for_path(&processed, duplicate, &duplicate_output_path, config) {
+                unprocessed_files.push(UnprocessedFile {
+                    path: duplicate.to_string_lossy().to_string(),
+                    reason: format!("Error writing duplicate output: {}", e),
+                });
+            }
+        }
+    }
+
     // Check for other files that aren't PDF or JSON (excluding target and .git)
     let all_files_pattern = format!("{}/**/*", canonical_input_dir.display());
     for entry in glob(&all_files_pattern)? {
@@ -371,10 +1696,10 @@ fn process_pdf_directory_with_structure(
                 }
                 
                 // Skip PDF and JSON files as they're already handled
-                if let Some(ext) = path.extension() {
-                    if ext == "pdf" || ext == "json" {
-                        continue;
-                    }
+                if let Some(ext) = path.extension()
+                    && (ext == "pdf" || ext == "json")
+                {
+                    continue;
                 }
                 
                 // Skip already processed files
@@ -396,58 +1721,41 @@ fn process_pdf_directory_with_structure(
             }
         }
     }
-    
+
+    if config.stats && config.preview.is_none() {
+        summary.files_failed = unprocessed_files
+            .iter()
+            .filter(|file| file.reason != "Unsupported file type")
+            .count();
+        summary.unprocessed_files = &unprocessed_files;
+        let summary_path = Path::new(output_dir).join("_summary_stats.json");
+        io::atomic_write(&summary_path, serde_json::to_string_pretty(&summary)?.as_bytes())?;
+        eprintln!("Summary stats saved to: {:?}", summary_path);
+    }
+
     Ok(unprocessed_files)
 }
 
-fn flatten_and_filter_blocks(blocks: Vec<Block>) -> Vec<Block> {
-    let mut result = Vec::new();
-    
-    for block in blocks {
-        // Skip page blocks as they are just containers
-        if block.block_type == "Page" {
-            // Process children of page blocks
-            if let Some(children) = block.children {
-                result.extend(flatten_and_filter_blocks(children));
-            }
-        } else {
-            // Filter out header, footer, picture, and list group blocks
-            if block.block_type != "PageHeader" 
-                && block.block_type != "PageFooter" 
-                && block.block_type != "Picture"
-                && block.block_type != "ListGroup" {
-                // Extract text from HTML
-                let text = extract_text_from_html(&block.html);
-                
-                // Remove polygon, bbox, children, section_hierarchy, and images fields
-                let filtered_block = Block {
-                    id: block.id,
-                    block_type: block.block_type,
-                    html: block.html,
-                    text,
-                    polygon: None,
-                    bbox: None,
-                    children: None,
-                    section_hierarchy: None,
-                    images: None,
-                };
-                result.push(filtered_block);
-            }
-        }
+// --keep-pictures and --keep-list-groups take their types off the exclude
+// list, whether it is the default one or given with --exclude-types
+fn filter_config(args: &Args) -> FilterConfig {
+    let mut excluded_types: Vec<String> = if args.exclude_types.is_empty() {
+        DEFAULT_EXCLUDED_TYPES.iter().map(|t| t.to_string()).collect()
+    } else {
+        args.exclude_types.clone()
+    };
+    if args.keep_pictures {
+        excluded_types.retain(|t| t != "Picture");
+    }
+    if args.keep_list_groups {
+        excluded_types.retain(|t| t != "ListGroup");
     }
-    
-    result
-}
 
-fn extract_text_from_html(html: &str) -> String {
-    // Create a regex to remove HTML tags
-    let re = Regex::new(r"<[^>]*>").unwrap();
-    
-    // Remove HTML tags
-    let text = re.replace_all(html, " ");
-    
-    // Clean up whitespace
-    text.split_whitespace().collect::<Vec<_>>().join(" ")
+    FilterConfig {
+        excluded_types,
+        included_types: (!args.include_only_types.is_empty()).then(|| args.include_only_types.clone()),
+        min_picture_area: args.min_picture_area,
+    }
 }
 
 fn determine_output_path(
@@ -460,10 +1768,7 @@ fn determine_output_path(
         let dir_path = Path::new(dir);
         
         // Get the file name
-        let file_name = input_path
-            .file_stem()
-            .and_then(|name| name.to_str())
-            .unwrap_or("output");
+        let file_name = file_stem(input_path);
         let output_file_name = format!("{}_processed.{}", file_name, extension);
         
         // For now, just put all files in the output directory
@@ -475,10 +1780,7 @@ fn determine_output_path(
         let parent_dir = input_path.parent().unwrap_or_else(|| Path::new("."));
         
         // Create output filename based on input
-        let file_name = input_path
-            .file_stem()
-            .and_then(|name| name.to_str())
-            .unwrap_or("output");
+        let file_name = file_stem(input_path);
         let output_file_name = format!("{}_processed.{}", file_name, extension);
         
         parent_dir.join(output_file_name)
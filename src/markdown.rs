@@ -0,0 +1,75 @@
+use crate::Block;
+use crate::headings::heading_level;
+use crate::tables::{
+    TableFormat, choose_table_format, column_widths, parse_table_rows, render_ascii_table,
+    render_definition_list, render_gfm_table,
+};
+
+/// Renders filtered blocks as Markdown, one block per paragraph. Tables are
+/// pipe tables unless `auto_table_format` picks a layout per table.
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::markdown::render_markdown;
+///
+/// let block = |block_type: &str, html: &str, text: &str| Block {
+///     block_type: block_type.to_string(),
+///     html: html.to_string(),
+///     text: text.to_string(),
+///     ..Default::default()
+/// };
+/// let blocks = [
+///     block("SectionHeader", "<h2>Results</h2>", "Results"),
+///     block("Text", "<p>All passed.</p>", "All passed."),
+///     block("ListItem", "<li>Repeat</li>", "Repeat"),
+/// ];
+/// assert_eq!(render_markdown(&blocks, false), "## Results\n\nAll passed.\n\n- Repeat\n");
+/// ```
+pub fn render_markdown(blocks: &[Block], auto_table_format: bool) -> String {
+    let mut sections = Vec::new();
+
+    for block in blocks {
+        let rendered = match block.block_type.as_str() {
+            "Title" | "SectionHeader" => {
+                let level = heading_level(&block.html).unwrap_or(match block.block_type.as_str() {
+                    "Title" => 1,
+                    _ => 2,
+                });
+                format!("{} {}", "#".repeat(level), block.text)
+            }
+            "ListItem" => format!("- {}", block.text),
+            "BlockQuote" => block
+                .text
+                .lines()
+                .map(|line| format!("> {}", line))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            "Table" => {
+                let rows = parse_table_rows(&block.html);
+                if rows.is_empty() {
+                    block.text.clone()
+                } else if auto_table_format {
+                    let widths = column_widths(&rows);
+                    match choose_table_format(widths.len(), &widths) {
+                        TableFormat::DefinitionList => render_definition_list(&rows),
+                        TableFormat::Gfm => render_gfm_table(&rows),
+                        // Fenced so Markdown renderers keep the grid aligned
+                        TableFormat::Ascii => format!("```\n{}\n```", render_ascii_table(&rows)),
+                    }
+                } else {
+                    render_gfm_table(&rows)
+                }
+            }
+            "Code" => format!("```\n{}\n```", block.text),
+            "DocumentBoundary" => "---".to_string(),
+            _ => block.text.clone(),
+        };
+
+        if !rendered.trim().is_empty() {
+            sections.push(rendered);
+        }
+    }
+
+    let mut markdown = sections.join("\n\n");
+    markdown.push('\n');
+    markdown
+}
@@ -0,0 +1,162 @@
+// Runs Marker on PDFs in a scratch directory and finds the JSON it wrote
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Lines of Marker's stderr kept in the error message, so the summary of
+// unprocessed files stays readable
+const STDERR_TAIL_LINES: usize = 5;
+
+#[derive(Debug)]
+pub enum MarkerError {
+    // The Marker executable could not be started
+    Spawn { bin: String, source: std::io::Error },
+    // Marker ran but exited unsuccessfully
+    Failed { status: Option<i32>, stderr: String },
+    // Marker exited successfully but did not write any of the expected JSON files
+    OutputNotFound { searched: Vec<PathBuf> },
+    // The directory Marker runs in could not be set up
+    WorkDir { path: PathBuf, source: std::io::Error },
+}
+
+impl fmt::Display for MarkerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarkerError::Spawn { bin, source } => write!(f, "could not run {}: {}", bin, source),
+            MarkerError::Failed { status, stderr } => {
+                match status {
+                    Some(code) => write!(f, "marker exited with status {}", code)?,
+                    None => write!(f, "marker was terminated by a signal")?,
+                }
+                let lines: Vec<&str> = stderr.lines().filter(|line| !line.trim().is_empty()).collect();
+                let tail = &lines[lines.len().saturating_sub(STDERR_TAIL_LINES)..];
+                if !tail.is_empty() {
+                    write!(f, ": {}", tail.join(" | "))?;
+                }
+                Ok(())
+            }
+            MarkerError::OutputNotFound { searched } => {
+                let searched: Vec<String> = searched.iter().map(|path| path.display().to_string()).collect();
+                write!(f, "marker output not found (looked for {})", searched.join(", "))
+            }
+            MarkerError::WorkDir { path, source } => {
+                write!(f, "could not prepare marker work directory {}: {}", path.display(), source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MarkerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MarkerError::Spawn { source, .. } | MarkerError::WorkDir { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+// Runs `<marker_bin> <pdf> --output_format json --output_dir <work_dir>` and
+// returns the path of the JSON it produced inside `work_dir`. Marker's
+// stderr is forwarded to ours.
+pub fn convert_pdf(marker_bin: &str, pdf_path: &Path, work_dir: &Path) -> Result<PathBuf, MarkerError> {
+    eprintln!("Running {} on {:?}", marker_bin, pdf_path);
+    run_marker(marker_bin, pdf_path, work_dir)?;
+    find_output(pdf_path, work_dir)
+}
+
+// Runs Marker's batch command once over `pdf_paths`, which must have
+// distinct file names (see `batches`): the PDFs are linked into
+// `<work_dir>/input` and converted into `<work_dir>/output`. Returns the
+// JSON path for each PDF in order, or the error for a PDF Marker skipped.
+// A batch that fails as a whole is one error.
+pub fn convert_pdf_batch(
+    marker_batch_bin: &str,
+    pdf_paths: &[PathBuf],
+    work_dir: &Path,
+) -> Result<Vec<Result<PathBuf, MarkerError>>, MarkerError> {
+    let input_dir = work_dir.join("input");
+    let output_dir = work_dir.join("output");
+    fs::create_dir_all(&input_dir).map_err(|source| MarkerError::WorkDir {
+        path: input_dir.clone(),
+        source,
+    })?;
+    for pdf_path in pdf_paths {
+        let staged = input_dir.join(pdf_path.file_name().unwrap_or_default());
+        fs::hard_link(pdf_path, &staged)
+            .or_else(|_| fs::copy(pdf_path, &staged).map(|_| ()))
+            .map_err(|source| MarkerError::WorkDir { path: staged, source })?;
+    }
+
+    eprintln!("Running {} on {} PDF(s)", marker_batch_bin, pdf_paths.len());
+    run_marker(marker_batch_bin, &input_dir, &output_dir)?;
+    Ok(pdf_paths
+        .iter()
+        .map(|pdf_path| find_output(pdf_path, &output_dir))
+        .collect())
+}
+
+// Splits PDFs into groups without repeated file names, in order, so each
+// group can be staged into one batch input directory
+pub fn batches(pdf_paths: Vec<PathBuf>) -> Vec<Vec<PathBuf>> {
+    let mut batches: Vec<Vec<PathBuf>> = Vec::new();
+    for pdf_path in pdf_paths {
+        let free = batches
+            .iter_mut()
+            .find(|batch| batch.iter().all(|other| other.file_name() != pdf_path.file_name()));
+        match free {
+            Some(batch) => batch.push(pdf_path),
+            None => batches.push(vec![pdf_path]),
+        }
+    }
+    batches
+}
+
+// A fresh directory for Marker to write into. Marker also writes images and
+// a `<stem>_meta.json` next to its JSON; all of it is removed with the
+// directory, so nothing is left in the input tree.
+pub fn work_dir() -> Result<tempfile::TempDir, MarkerError> {
+    tempfile::Builder::new()
+        .prefix("flatten_marker_output")
+        .tempdir()
+        .map_err(|source| MarkerError::WorkDir {
+            path: std::env::temp_dir(),
+            source,
+        })
+}
+
+fn run_marker(bin: &str, input: &Path, output_dir: &Path) -> Result<(), MarkerError> {
+    let output = Command::new(bin)
+        .arg(input)
+        .args(["--output_format", "json", "--output_dir"])
+        .arg(output_dir)
+        .output()
+        .map_err(|source| MarkerError::Spawn {
+            bin: bin.to_string(),
+            source,
+        })?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    if !stderr.is_empty() {
+        eprint!("{}", stderr);
+    }
+    if !output.status.success() {
+        return Err(MarkerError::Failed {
+            status: output.status.code(),
+            stderr,
+        });
+    }
+    Ok(())
+}
+
+// Marker writes <dir>/<stem>/<stem>.json; older versions write <dir>/<stem>.json
+fn find_output(pdf_path: &Path, output_dir: &Path) -> Result<PathBuf, MarkerError> {
+    let stem = crate::file_stem(pdf_path);
+    let file_name = format!("{}.json", stem);
+    let searched = vec![output_dir.join(stem).join(&file_name), output_dir.join(file_name)];
+    searched
+        .iter()
+        .find(|path| path.is_file())
+        .cloned()
+        .ok_or(MarkerError::OutputNotFound { searched })
+}
@@ -0,0 +1,121 @@
+use crate::Block;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct Ngram<'a> {
+    pub text: &'a str,
+}
+
+/// Joins the text of every block with at least `min_chars` characters,
+/// separated by newlines
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::ngrams::corpus_text;
+///
+/// let text = |text: &str| Block { text: text.to_string(), ..Default::default() };
+/// assert_eq!(corpus_text(&[text("Long enough"), text("no"), text("Also long")], 5), "Long enough\nAlso long");
+/// ```
+pub fn corpus_text(blocks: &[Block], min_chars: usize) -> String {
+    blocks
+        .iter()
+        .filter(|block| block.text.chars().count() >= min_chars)
+        .map(|block| block.text.as_str())
+        .collect::<Vec<&str>>()
+        .join("\n")
+}
+
+/// Windows of exactly `n` characters starting every `stride` characters.
+/// A trailing window shorter than `n` is not emitted, so a text of L
+/// characters yields (L - n) / stride + 1 windows when L >= n and none
+/// otherwise.
+/// ```
+/// use pdf_parser::ngrams::char_ngrams;
+///
+/// assert_eq!(char_ngrams("abcdefg", 3, 2), ["abc", "cde", "efg"]);
+/// assert_eq!(char_ngrams("añoñu", 2, 2), ["añ", "oñ"]);
+/// ```
+pub fn char_ngrams(text: &str, n: usize, stride: usize) -> Vec<&str> {
+    // Byte offset of every character, plus the end of the text
+    let boundaries: Vec<usize> = text
+        .char_indices()
+        .map(|(offset, _)| offset)
+        .chain(std::iter::once(text.len()))
+        .collect();
+    let char_count = boundaries.len() - 1;
+    if n == 0 || stride == 0 || char_count < n {
+        return Vec::new();
+    }
+
+    (0..=char_count - n)
+        .step_by(stride)
+        .map(|start| &text[boundaries[start]..boundaries[start + n]])
+        .collect()
+}
+
+/// One `{"text": ...}` JSON object per line
+/// ```
+/// use pdf_parser::ngrams::to_jsonl;
+///
+/// assert_eq!(to_jsonl(&["ab", "c\"d"])?, "{\"text\":\"ab\"}\n{\"text\":\"c\\\"d\"}\n");
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub fn to_jsonl(ngrams: &[&str]) -> Result<String, serde_json::Error> {
+    let mut output = String::new();
+    for &text in ngrams {
+        output.push_str(&serde_json::to_string(&Ngram { text })?);
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(text: &str) -> Block {
+        Block {
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn window_count_follows_the_formula() {
+        let text = "abcdefghij";
+        assert_eq!(char_ngrams(text, 4, 2), ["abcd", "cdef", "efgh", "ghij"]);
+        // (10 - 4) / 3 + 1 = 3; the trailing "j" window is shorter than n
+        assert_eq!(char_ngrams(text, 4, 3), ["abcd", "defg", "ghij"]);
+        assert_eq!(char_ngrams(text, 3, 4), ["abc", "efg"]);
+        assert_eq!(char_ngrams(text, 10, 1), [text]);
+    }
+
+    #[test]
+    fn windows_count_characters_not_bytes() {
+        assert_eq!(char_ngrams("ñañá日本", 3, 2), ["ñañ", "ñá日"]);
+        assert_eq!(char_ngrams("ñañá日本", 2, 2), ["ña", "ñá", "日本"]);
+    }
+
+    #[test]
+    fn degenerate_parameters_yield_nothing() {
+        assert!(char_ngrams("abc", 4, 1).is_empty());
+        assert!(char_ngrams("abc", 0, 1).is_empty());
+        assert!(char_ngrams("abc", 2, 0).is_empty());
+        assert!(char_ngrams("", 1, 1).is_empty());
+    }
+
+    #[test]
+    fn corpus_skips_short_blocks() {
+        let blocks = [block("Long enough block"), block("short"), block("Another long one")];
+        assert_eq!(corpus_text(&blocks, 10), "Long enough block\nAnother long one");
+        assert_eq!(corpus_text(&blocks, 0), "Long enough block\nshort\nAnother long one");
+    }
+
+    #[test]
+    fn jsonl_has_one_object_per_line() {
+        assert_eq!(
+            to_jsonl(&["a\"b", "c\nd"]).unwrap(),
+            "{\"text\":\"a\\\"b\"}\n{\"text\":\"c\\nd\"}\n"
+        );
+        assert_eq!(to_jsonl(&[]).unwrap(), "");
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{Block, page_from_id};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+// Block types that mean a page carries an image
+const IMAGE_BLOCK_TYPES: [&str; 3] = ["Picture", "Figure", "PictureGroup"];
+
+#[derive(Serialize, Debug)]
+pub struct PageSummary {
+    pub page: u32,
+    pub block_count: usize,
+    pub blocks_by_type: BTreeMap<String, usize>,
+    pub word_count: usize,
+    pub table_count: usize,
+    pub headings: Vec<String>,
+    pub has_images: bool,
+}
+
+/// One summary per page that has processed blocks, in page order. Pages are
+/// taken from the block ids; blocks whose id has no page are not counted.
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::page_summary::summarize_pages;
+///
+/// let block = |id: &str, block_type: &str, text: &str| Block {
+///     id: id.to_string(),
+///     block_type: block_type.to_string(),
+///     text: text.to_string(),
+///     ..Default::default()
+/// };
+/// let pages = summarize_pages(&[
+///     block("/page/1/SectionHeader/0", "SectionHeader", "Methods"),
+///     block("/page/1/Text/1", "Text", "Three words here"),
+///     block("/page/0/Picture/0", "Picture", ""),
+/// ]);
+/// assert_eq!(pages.len(), 2);
+/// assert!(pages[0].has_images);
+/// assert_eq!((pages[1].page, pages[1].word_count), (1, 4));
+/// assert_eq!(pages[1].headings, ["Methods"]);
+/// ```
+pub fn summarize_pages(blocks: &[Block]) -> Vec<PageSummary> {
+    let mut pages: BTreeMap<u32, PageSummary> = BTreeMap::new();
+
+    for block in blocks {
+        let Some(page) = page_from_id(&block.id) else {
+            continue;
+        };
+        let summary = pages.entry(page).or_insert_with(|| PageSummary {
+            page,
+            block_count: 0,
+            blocks_by_type: BTreeMap::new(),
+            word_count: 0,
+            table_count: 0,
+            headings: Vec::new(),
+            has_images: false,
+        });
+
+        summary.block_count += 1;
+        *summary.blocks_by_type.entry(block.block_type.clone()).or_insert(0) += 1;
+        summary.word_count += block.text.split_whitespace().count();
+        match block.block_type.as_str() {
+            "Table" => summary.table_count += 1,
+            "Title" | "SectionHeader" => summary.headings.push(block.text.trim().to_string()),
+            _ => {}
+        }
+        if IMAGE_BLOCK_TYPES.contains(&block.block_type.as_str())
+            || block.images.as_ref().is_some_and(|images| !images.is_null())
+        {
+            summary.has_images = true;
+        }
+    }
+
+    pages.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(id: &str, block_type: &str, text: &str) -> Block {
+        Block {
+            id: id.to_string(),
+            block_type: block_type.to_string(),
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn blocks_are_grouped_by_the_page_in_their_id() {
+        let blocks = [
+            block("/page/0/SectionHeader/0", "SectionHeader", " Introduction "),
+            block("/page/0/Text/1", "Text", "Three words here"),
+            block("/page/0/Text/2", "Text", "and two"),
+            block("/page/2/Table/0", "Table", "a b\tc"),
+            block("/page/2/Picture/1", "Picture", ""),
+            block("/page/1/Title/0", "Title", "Part One"),
+        ];
+
+        let pages = summarize_pages(&blocks);
+        assert_eq!(pages.iter().map(|page| page.page).collect::<Vec<_>>(), [0, 1, 2]);
+
+        let first = &pages[0];
+        assert_eq!(first.block_count, 3);
+        assert_eq!(first.blocks_by_type["Text"], 2);
+        assert_eq!(first.blocks_by_type["SectionHeader"], 1);
+        assert_eq!(first.word_count, 6);
+        assert_eq!(first.table_count, 0);
+        assert_eq!(first.headings, ["Introduction"]);
+        assert!(!first.has_images);
+
+        assert_eq!(pages[1].headings, ["Part One"]);
+
+        let last = &pages[2];
+        assert_eq!(last.table_count, 1);
+        assert_eq!(last.word_count, 3);
+        assert!(last.has_images);
+    }
+
+    #[test]
+    fn image_data_marks_a_page_as_having_images() {
+        let mut with_image = block("/page/3/Figure/0", "Text", "Caption");
+        with_image.images = Some(serde_json::json!({ "fig.png": "..." }));
+        let mut null_image = block("/page/4/Text/0", "Text", "Plain");
+        null_image.images = Some(serde_json::Value::Null);
+
+        let pages = summarize_pages(&[with_image, null_image]);
+        assert!(pages[0].has_images);
+        assert!(!pages[1].has_images);
+    }
+
+    #[test]
+    fn blocks_without_a_page_are_skipped() {
+        let blocks = [block("custom-id", "Text", "Orphan"), block("/page/x/Text/0", "Text", "Bad page")];
+        assert!(summarize_pages(&blocks).is_empty());
+    }
+}
@@ -0,0 +1,178 @@
+// Parquet serialization of a block list for analytics tools (Pandas, DuckDB,
+// Polars). Columns: id, block_type (dictionary-encoded), text, page (parsed
+// from the block id, null when the id has none) and source_file.
+use crate::{Block, page_from_id};
+use arrow2::array::{Array, DictionaryArray, Int32Array, MutableDictionaryArray, MutableUtf8Array, TryExtend, Utf8Array};
+use arrow2::chunk::Chunk;
+use arrow2::datatypes::{DataType, Field, IntegerType, Schema};
+use arrow2::error::Error;
+use arrow2::io::parquet::write::{
+    CompressionOptions, Encoding, FileWriter, RowGroupIterator, Version, WriteOptions, transverse,
+};
+
+// Rows per Parquet row group
+pub const ROW_GROUP_SIZE: usize = 10_000;
+
+/// Columns of the Parquet output
+/// ```
+/// use pdf_parser::parquet_format::parquet_schema;
+///
+/// let schema = parquet_schema();
+/// let names: Vec<&str> = schema.fields.iter().map(|field| field.name.as_str()).collect();
+/// assert_eq!(names, ["id", "block_type", "text", "page", "source_file"]);
+/// ```
+pub fn parquet_schema() -> Schema {
+    Schema::from(vec![
+        Field::new("id", DataType::Utf8, false),
+        Field::new(
+            "block_type",
+            DataType::Dictionary(IntegerType::Int8, Box::new(DataType::Utf8), false),
+            false,
+        ),
+        Field::new("text", DataType::Utf8, false),
+        Field::new("page", DataType::Int32, true),
+        Field::new("source_file", DataType::Utf8, false),
+    ])
+}
+
+/// Writes the blocks as a Snappy-compressed Parquet file, ROW_GROUP_SIZE rows
+/// per row group. Fails if the blocks have more distinct block types than an
+/// Int8 dictionary key can index.
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::parquet_format::blocks_to_parquet;
+///
+/// let block = Block {
+///     id: "/page/0/Text/0".to_string(),
+///     block_type: "Text".to_string(),
+///     text: "Body".to_string(),
+///     ..Default::default()
+/// };
+/// let bytes = blocks_to_parquet(&[block], "doc.json")?;
+/// assert!(bytes.starts_with(b"PAR1") && bytes.ends_with(b"PAR1"));
+/// # Ok::<(), arrow2::error::Error>(())
+/// ```
+pub fn blocks_to_parquet(blocks: &[Block], source_file: &str) -> Result<Vec<u8>, Error> {
+    let schema = parquet_schema();
+    let options = WriteOptions {
+        write_statistics: true,
+        compression: CompressionOptions::Snappy,
+        version: Version::V2,
+        data_pagesize_limit: None,
+    };
+    let encodings = schema
+        .fields
+        .iter()
+        .map(|field| {
+            transverse(&field.data_type, |data_type| match data_type {
+                DataType::Dictionary(..) => Encoding::RleDictionary,
+                _ => Encoding::Plain,
+            })
+        })
+        .collect();
+
+    let chunks = blocks
+        .chunks(ROW_GROUP_SIZE)
+        .map(|rows| block_chunk(rows, source_file))
+        .collect::<Vec<_>>();
+    let row_groups = RowGroupIterator::try_new(chunks.into_iter(), &schema, options, encodings)?;
+
+    let mut writer = FileWriter::try_new(Vec::new(), schema, options)?;
+    for group in row_groups {
+        writer.write(group?)?;
+    }
+    writer.end(None)?;
+    Ok(writer.into_inner())
+}
+
+fn block_chunk(blocks: &[Block], source_file: &str) -> Result<Chunk<Box<dyn Array>>, Error> {
+    let ids = Utf8Array::<i32>::from_trusted_len_values_iter(blocks.iter().map(|block| &block.id));
+
+    let mut block_types = MutableDictionaryArray::<i8, MutableUtf8Array<i32>>::new();
+    block_types.try_extend(blocks.iter().map(|block| Some(block.block_type.as_str())))?;
+
+    let texts = Utf8Array::<i32>::from_trusted_len_values_iter(blocks.iter().map(|block| &block.text));
+    let pages = Int32Array::from_trusted_len_iter(
+        blocks
+            .iter()
+            .map(|block| page_from_id(&block.id).and_then(|page| i32::try_from(page).ok())),
+    );
+    let sources = Utf8Array::<i32>::from_trusted_len_values_iter(blocks.iter().map(|_| source_file));
+
+    Chunk::try_new(vec![
+        ids.boxed(),
+        DictionaryArray::from(block_types).boxed(),
+        texts.boxed(),
+        pages.boxed(),
+        sources.boxed(),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow2::io::parquet::read;
+    use std::io::Cursor;
+
+    fn block(index: usize) -> Block {
+        Block {
+            id: format!("/page/{}/Text/{}", index / 50, index),
+            block_type: if index.is_multiple_of(7) { "SectionHeader" } else { "Text" }.to_string(),
+            text: format!("Block {}", index),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn round_trips_row_count_across_row_groups() {
+        let blocks: Vec<Block> = (0..25_000).map(block).collect();
+        let bytes = blocks_to_parquet(&blocks, "paper.json").unwrap();
+
+        let mut reader = Cursor::new(bytes);
+        let metadata = read::read_metadata(&mut reader).unwrap();
+        assert_eq!(metadata.num_rows, 25_000);
+        assert_eq!(metadata.row_groups.len(), 3);
+
+        let schema = read::infer_schema(&metadata).unwrap();
+        let names: Vec<&str> = schema.fields.iter().map(|field| field.name.as_str()).collect();
+        assert_eq!(names, ["id", "block_type", "text", "page", "source_file"]);
+
+        let chunks = read::FileReader::new(reader, metadata.row_groups, schema, None, None, None);
+        let rows: usize = chunks.map(|chunk| chunk.unwrap().len()).sum();
+        assert_eq!(rows, 25_000);
+    }
+
+    #[test]
+    fn columns_hold_block_values() {
+        let blocks = vec![
+            block(0),
+            Block {
+                id: "no-page".to_string(),
+                block_type: "Text".to_string(),
+                text: "Loose".to_string(),
+                ..Default::default()
+            },
+        ];
+        let bytes = blocks_to_parquet(&blocks, "paper.json").unwrap();
+
+        let mut reader = Cursor::new(bytes);
+        let metadata = read::read_metadata(&mut reader).unwrap();
+        let schema = read::infer_schema(&metadata).unwrap();
+        assert_eq!(schema, parquet_schema());
+
+        let mut chunks = read::FileReader::new(reader, metadata.row_groups, schema, None, None, None);
+        let chunk = chunks.next().unwrap().unwrap();
+        let columns = chunk.arrays();
+
+        let texts = columns[2].as_any().downcast_ref::<Utf8Array<i32>>().unwrap();
+        assert_eq!(texts.value(0), "Block 0");
+        assert_eq!(texts.value(1), "Loose");
+
+        let pages = columns[3].as_any().downcast_ref::<Int32Array>().unwrap();
+        assert_eq!(pages.get(0), Some(0));
+        assert_eq!(pages.get(1), None);
+
+        let sources = columns[4].as_any().downcast_ref::<Utf8Array<i32>>().unwrap();
+        assert_eq!(sources.value(1), "paper.json");
+    }
+}
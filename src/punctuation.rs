@@ -0,0 +1,196 @@
+use crate::Block;
+use serde::Serialize;
+
+// Longest run of a single punctuation character that is still plausible in
+// real text (e.g. "....." as a leader); anything longer is treated as noise
+const MAX_PUNCTUATION_RUN: usize = 5;
+
+// Blocks shorter than this are not judged on their ratio alone, so list
+// markers like "1." or headings like "I." are not flagged
+const MIN_CHARS_FOR_RATIO: usize = 10;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PunctuationFlag {
+    // Position of the block in the slice passed to `find_punctuation_noise`
+    pub index: usize,
+    pub id: String,
+    pub ratio: f64,
+    pub run: Option<(char, usize)>,
+}
+
+/// ASCII punctuation, Spanish and typographic marks, and the General
+/// Punctuation block (dashes, curly quotes, ellipses, ...)
+/// ```
+/// use pdf_parser::punctuation::is_punctuation;
+///
+/// assert!(is_punctuation('.') && is_punctuation('¿'));
+/// assert!(!is_punctuation('a'));
+/// ```
+pub fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation()
+        || matches!(c, '¡' | '¿' | '«' | '»' | '·' | '§' | '¶')
+        || ('\u{2010}'..='\u{205e}').contains(&c)
+}
+
+/// Fraction of characters in `text` that are punctuation
+/// ```
+/// use pdf_parser::punctuation::punctuation_ratio;
+///
+/// assert_eq!(punctuation_ratio("a.b."), 0.5);
+/// assert_eq!(punctuation_ratio(""), 0.0);
+/// ```
+pub fn punctuation_ratio(text: &str) -> f64 {
+    let total = text.chars().count();
+    if total == 0 {
+        return 0.0;
+    }
+    let punctuation = text.chars().filter(|&c| is_punctuation(c)).count();
+    punctuation as f64 / total as f64
+}
+
+/// Longest run of one repeated punctuation character, if any is longer than
+/// MAX_PUNCTUATION_RUN
+/// ```
+/// use pdf_parser::punctuation::longest_punctuation_run;
+///
+/// assert_eq!(longest_punctuation_run("Wait........ what"), Some(('.', 8)));
+/// assert_eq!(longest_punctuation_run("Chapter 1 ..... 12"), None);
+/// ```
+pub fn longest_punctuation_run(text: &str) -> Option<(char, usize)> {
+    let mut longest: Option<(char, usize)> = None;
+    let mut current: Option<(char, usize)> = None;
+
+    for c in text.chars() {
+        current = match current {
+            Some((prev, count)) if prev == c => Some((c, count + 1)),
+            _ if is_punctuation(c) => Some((c, 1)),
+            _ => None,
+        };
+        if let Some((c, count)) = current
+            && longest.is_none_or(|(_, max)| count > max)
+        {
+            longest = Some((c, count));
+        }
+    }
+
+    longest.filter(|&(_, count)| count > MAX_PUNCTUATION_RUN)
+}
+
+/// Returns a flag for every block whose punctuation ratio exceeds `threshold`
+/// or that contains an implausibly long run of one punctuation character, in
+/// block order
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::punctuation::find_punctuation_noise;
+///
+/// let text = |text: &str| Block { text: text.to_string(), ..Default::default() };
+/// let flags = find_punctuation_noise(&[text("A normal sentence."), text("~~~~~~~~ ## ~~")], 0.15);
+/// assert_eq!(flags.len(), 1);
+/// assert_eq!(flags[0].index, 1);
+/// ```
+pub fn find_punctuation_noise(blocks: &[Block], threshold: f64) -> Vec<PunctuationFlag> {
+    blocks
+        .iter()
+        .enumerate()
+        .filter_map(|(index, block)| {
+            let ratio = punctuation_ratio(&block.text);
+            let run = longest_punctuation_run(&block.text);
+            let noisy_ratio = block.text.chars().count() >= MIN_CHARS_FOR_RATIO && ratio > threshold;
+            if noisy_ratio || run.is_some() {
+                Some(PunctuationFlag {
+                    index,
+                    id: block.id.clone(),
+                    ratio,
+                    run,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Removes the flagged blocks. Flags are matched by position rather than id,
+/// since blocks without an id all share the empty one.
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::punctuation::{drop_flagged_blocks, find_punctuation_noise};
+///
+/// let text = |text: &str| Block { text: text.to_string(), ..Default::default() };
+/// let mut blocks = vec![text("~~~~~~~~ ## ~~"), text("A normal sentence.")];
+/// let flags = find_punctuation_noise(&blocks, 0.15);
+/// drop_flagged_blocks(&mut blocks, &flags);
+/// assert_eq!(blocks.len(), 1);
+/// assert_eq!(blocks[0].text, "A normal sentence.");
+/// ```
+pub fn drop_flagged_blocks(blocks: &mut Vec<Block>, flags: &[PunctuationFlag]) {
+    let mut flagged = flags.iter().map(|flag| flag.index).peekable();
+    let mut index = 0;
+    blocks.retain(|_| {
+        let keep = flagged.next_if_eq(&index).is_none();
+        index += 1;
+        keep
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(id: &str, text: &str) -> Block {
+        Block {
+            id: id.to_string(),
+            block_type: "Text".to_string(),
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn clean_text_is_not_flagged() {
+        let blocks = [
+            block("a", "An ordinary sentence, with commas; and a full stop."),
+            block("b", "1."),
+            block("c", "The results were consistent across all trials."),
+        ];
+        assert!(find_punctuation_noise(&blocks, 0.15).is_empty());
+    }
+
+    #[test]
+    fn noisy_ratio_and_long_runs_are_flagged_with_their_index() {
+        let blocks = [
+            block("a", "Clean text here."),
+            block("b", "|| -- ;; ,, '' :: !!"),
+            block("c", "Chapter one ________ begins"),
+        ];
+        let flags = find_punctuation_noise(&blocks, 0.15);
+
+        assert_eq!(flags.iter().map(|flag| flag.index).collect::<Vec<_>>(), [1, 2]);
+        assert!(flags[0].ratio > 0.5);
+        assert_eq!(flags[0].run, None);
+        assert_eq!(flags[1].id, "c");
+        assert_eq!(flags[1].run, Some(('_', 8)));
+    }
+
+    #[test]
+    fn dropping_by_index_keeps_unflagged_blocks_with_the_same_id() {
+        let mut blocks = vec![
+            block("", "Kept paragraph without an id."),
+            block("", "#*#*#*#*#*#*#*#*"),
+            block("", "Another kept paragraph."),
+            block("x", "~~~~~~~~~~~~"),
+        ];
+        let flags = find_punctuation_noise(&blocks, 0.15);
+        drop_flagged_blocks(&mut blocks, &flags);
+
+        let texts: Vec<&str> = blocks.iter().map(|block| block.text.as_str()).collect();
+        assert_eq!(texts, ["Kept paragraph without an id.", "Another kept paragraph."]);
+    }
+
+    #[test]
+    fn punctuation_ratio_counts_characters() {
+        assert_eq!(punctuation_ratio(""), 0.0);
+        assert_eq!(punctuation_ratio("ab!?"), 0.5);
+        assert_eq!(punctuation_ratio("«»"), 1.0);
+    }
+}
@@ -0,0 +1,225 @@
+// Streaming reader for Marker JSON. serde_json::from_str needs the whole
+// file in memory as a String and then builds every page on top of it;
+// reading from a BufReader never buffers the file, and pages are read one at
+// a time so the base64 image data Marker embeds in each block can be dropped
+// before the next page is read. read_pages goes further and never holds more
+// than one page: each is handed to the caller, to be processed and written
+// out, before the next is parsed.
+use crate::{Block, Document};
+use serde::Deserializer as _;
+use serde::de::{DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use std::fmt;
+use std::io::Read;
+
+/// Reads a Marker document from `reader`. Unless `keep_images` is set, the
+/// `images` field of every block is discarded as its page is parsed, so peak
+/// memory is bounded by the largest page instead of the whole document.
+/// Top-level fields other than `children` are skipped without being built.
+/// ```
+/// use pdf_parser::reader::read_document;
+///
+/// let json = r#"{"metadata": {"pages": 1}, "children": [{"id": "/page/0/Page/0", "block_type": "Page",
+///     "html": "", "images": {"a.png": "iVBORw0KGgo="}, "children": []}]}"#;
+/// let document = read_document(json.as_bytes(), false)?;
+/// assert_eq!(document.children[0].id, "/page/0/Page/0");
+/// assert!(document.children[0].images.is_none());
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub fn read_document<R: Read>(reader: R, keep_images: bool) -> serde_json::Result<Document> {
+    let mut children = Vec::new();
+    read_pages(reader, keep_images, |page| {
+        children.push(page);
+        Ok::<(), serde_json::Error>(())
+    })?;
+    Ok(Document { children })
+}
+
+/// Like read_document, but hands each page to `on_page` as soon as it is
+/// parsed instead of collecting them, so only one page is in memory at a
+/// time. Pages are passed in document order. Reading stops at the first
+/// error `on_page` returns, and that error is returned as is.
+/// ```
+/// use pdf_parser::reader::read_pages;
+///
+/// let json = r#"{"children": [
+///     {"id": "/page/0/Page/0", "block_type": "Page", "html": ""},
+///     {"id": "/page/1/Page/0", "block_type": "Page", "html": ""}
+/// ]}"#;
+/// let mut ids = Vec::new();
+/// read_pages(json.as_bytes(), false, |page| {
+///     ids.push(page.id);
+///     Ok::<(), serde_json::Error>(())
+/// })?;
+/// assert_eq!(ids, ["/page/0/Page/0", "/page/1/Page/0"]);
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub fn read_pages<R, F, E>(reader: R, keep_images: bool, on_page: F) -> Result<(), E>
+where
+    R: Read,
+    F: FnMut(Block) -> Result<(), E>,
+    E: From<serde_json::Error>,
+{
+    // serde can't carry `on_page`'s error type, so it is kept here and the
+    // parse is aborted with a placeholder error
+    let mut page_error = None;
+    let visitor = DocumentVisitor {
+        pages: PagesSeed {
+            keep_images,
+            on_page,
+            page_error: &mut page_error,
+        },
+    };
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let result = deserializer
+        .deserialize_map(visitor)
+        .and_then(|()| deserializer.end());
+    match (result, page_error) {
+        (_, Some(e)) => Err(e),
+        (Err(e), None) => Err(e.into()),
+        (Ok(()), None) => Ok(()),
+    }
+}
+
+struct DocumentVisitor<'a, F, E> {
+    pages: PagesSeed<'a, F, E>,
+}
+
+impl<'de, F, E> Visitor<'de> for DocumentVisitor<'_, F, E>
+where
+    F: FnMut(Block) -> Result<(), E>,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a Marker document object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let mut pages = Some(self.pages);
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "children" {
+                let seed = pages
+                    .take()
+                    .ok_or_else(|| serde::de::Error::duplicate_field("children"))?;
+                map.next_value_seed(seed)?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        if pages.is_some() {
+            return Err(serde::de::Error::missing_field("children"));
+        }
+        Ok(())
+    }
+}
+
+struct PagesSeed<'a, F, E> {
+    keep_images: bool,
+    on_page: F,
+    page_error: &'a mut Option<E>,
+}
+
+impl<'de, F, E> DeserializeSeed<'de> for PagesSeed<'_, F, E>
+where
+    F: FnMut(Block) -> Result<(), E>,
+{
+    type Value = ();
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, F, E> Visitor<'de> for PagesSeed<'_, F, E>
+where
+    F: FnMut(Block) -> Result<(), E>,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array of blocks")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(mut page) = seq.next_element::<Block>()? {
+            if !self.keep_images {
+                drop_images(std::slice::from_mut(&mut page));
+            }
+            if let Err(e) = (self.on_page)(page) {
+                *self.page_error = Some(e);
+                return Err(serde::de::Error::custom("page handler failed"));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn drop_images(blocks: &mut [Block]) {
+    for block in blocks {
+        block.images = None;
+        if let Some(children) = &mut block.children {
+            drop_images(children);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCUMENT: &str = r#"{
+        "metadata": {"pages": 2},
+        "children": [
+            {"id": "/page/0/Page/0", "block_type": "Page", "html": "", "images": {"a.png": "iVBORw0KGgo="}, "children": [
+                {"id": "/page/0/Picture/1", "block_type": "Picture", "html": "", "images": {"b.png": "iVBORw0KGgo="}}
+            ]},
+            {"id": "/page/1/Page/0", "block_type": "Page", "html": ""}
+        ]
+    }"#;
+
+    #[test]
+    fn pages_are_read_in_order_without_images() {
+        let mut pages = Vec::new();
+        read_pages(DOCUMENT.as_bytes(), false, |page| {
+            pages.push(page);
+            Ok::<(), serde_json::Error>(())
+        })
+        .unwrap();
+
+        let ids: Vec<&str> = pages.iter().map(|page| page.id.as_str()).collect();
+        assert_eq!(ids, ["/page/0/Page/0", "/page/1/Page/0"]);
+        assert!(pages[0].images.is_none());
+        assert!(pages[0].children.as_ref().unwrap()[0].images.is_none());
+    }
+
+    #[test]
+    fn images_are_kept_on_request() {
+        let document = read_document(DOCUMENT.as_bytes(), true).unwrap();
+        assert!(document.children[0].images.is_some());
+        assert!(document.children[0].children.as_ref().unwrap()[0].images.is_some());
+    }
+
+    #[test]
+    fn handler_errors_stop_reading_and_are_returned() {
+        let mut calls = 0;
+        let result = read_pages(DOCUMENT.as_bytes(), false, |_| {
+            calls += 1;
+            Err::<(), Box<dyn std::error::Error>>("disk full".into())
+        });
+        assert_eq!(result.unwrap_err().to_string(), "disk full");
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn malformed_documents_are_rejected() {
+        for json in [
+            r#"[{"id": "a", "block_type": "Text", "html": ""}]"#,
+            r#"{"metadata": {}}"#,
+            r#"{"children": [], "children": []}"#,
+            r#"{"children": []} trailing"#,
+            r#"{"children": [{"id": "a", "block_type": "Page", "html": ""}"#,
+        ] {
+            assert!(read_document(json.as_bytes(), false).is_err(), "{}", json);
+        }
+    }
+}
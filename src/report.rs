@@ -0,0 +1,212 @@
+// Self-contained HTML report of one processing run: a summary table, a
+// per-page breakdown, preview cards of the first blocks of each type, the
+// blocks that did not make it into the output and a block type histogram
+use pdf_parser::page_summary::summarize_pages;
+use pdf_parser::{
+    Block, ProcessingConfig, block_type_histogram, content_blocks, escape_html, is_small_picture, truncate_text,
+};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const PREVIEW_BLOCKS_PER_TYPE: usize = 5;
+const PREVIEW_TEXT_LENGTH: usize = 300;
+
+const HISTOGRAM_BAR_HEIGHT: usize = 22;
+const HISTOGRAM_LABEL_WIDTH: usize = 140;
+const HISTOGRAM_BAR_WIDTH: usize = 400;
+
+const STYLE: &str = "
+body { font-family: system-ui, sans-serif; margin: 2em auto; max-width: 960px; color: #222; }
+h1 { font-size: 1.6em; word-break: break-all; }
+h2 { border-bottom: 1px solid #ddd; padding-bottom: 0.2em; margin-top: 2em; }
+table { border-collapse: collapse; width: 100%; }
+th, td { border: 1px solid #ddd; padding: 0.3em 0.6em; text-align: left; vertical-align: top; }
+th { background: #f4f4f4; }
+td.number { text-align: right; }
+.cards { display: flex; flex-wrap: wrap; gap: 0.8em; }
+.card { border: 1px solid #ddd; border-radius: 6px; padding: 0.6em; width: 280px; background: #fafafa; }
+.card .id { font-family: monospace; font-size: 0.8em; color: #666; word-break: break-all; }
+.card p { margin: 0.4em 0 0; white-space: pre-wrap; }
+svg text { font-size: 12px; }
+";
+
+pub struct RunSummary<'a> {
+    pub input_path: &'a Path,
+    pub input_size: u64,
+    pub output_files: Vec<(PathBuf, u64)>,
+    pub elapsed: Duration,
+}
+
+pub fn render_report(
+    run: &RunSummary,
+    raw_blocks: &[Block],
+    blocks: &[Block],
+    config: &ProcessingConfig,
+) -> String {
+    let input_blocks = content_blocks(raw_blocks);
+    let output_ids: HashSet<&str> = blocks.iter().map(|block| block.id.as_str()).collect();
+    let type_counts = block_type_histogram(blocks);
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!(
+        "<title>Extraction report: {}</title>\n<style>{}</style>\n</head>\n<body>\n",
+        escape_html(&run.input_path.display().to_string()),
+        STYLE
+    ));
+    html.push_str(&format!(
+        "<h1>Extraction report: {}</h1>\n",
+        escape_html(&run.input_path.display().to_string())
+    ));
+
+    // Summary
+    html.push_str("<h2>Summary</h2>\n<table class=\"summary\">\n");
+    summary_row(&mut html, "Input blocks", &input_blocks.len().to_string());
+    summary_row(&mut html, "Output blocks", &blocks.len().to_string());
+    summary_row(&mut html, "Input file size", &format_size(run.input_size));
+    for (path, size) in &run.output_files {
+        let label = format!("Output file size ({})", path.display());
+        summary_row(&mut html, &label, &format_size(*size));
+    }
+    summary_row(
+        &mut html,
+        "Processing time",
+        &format!("{:.1} ms", run.elapsed.as_secs_f64() * 1000.0),
+    );
+    html.push_str("</table>\n");
+
+    // Histogram
+    html.push_str("<h2>Block types</h2>\n");
+    html.push_str(&render_histogram(&type_counts));
+
+    // Pages
+    html.push_str("<h2>Pages</h2>\n<table class=\"pages\">\n");
+    html.push_str("<tr><th>Page</th><th>Blocks</th><th>Words</th><th>Tables</th><th>Images</th><th>Headings</th></tr>\n");
+    for page in summarize_pages(blocks) {
+        html.push_str(&format!(
+            "<tr><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td>{}</td><td>{}</td></tr>\n",
+            page.page,
+            page.block_count,
+            page.word_count,
+            page.table_count,
+            if page.has_images { "yes" } else { "no" },
+            escape_html(&page.headings.join(" · "))
+        ));
+    }
+    html.push_str("</table>\n");
+
+    // Preview cards
+    html.push_str("<h2>Block previews</h2>\n");
+    for block_type in type_counts.keys() {
+        html.push_str(&format!("<h3>{}</h3>\n<div class=\"cards\">\n", escape_html(block_type)));
+        for block in blocks
+            .iter()
+            .filter(|block| &block.block_type == block_type)
+            .take(PREVIEW_BLOCKS_PER_TYPE)
+        {
+            html.push_str(&format!(
+                "<div class=\"card\"><div class=\"id\">{}</div><p>{}</p></div>\n",
+                escape_html(&block.id),
+                escape_html(&truncate_text(&block.text, PREVIEW_TEXT_LENGTH))
+            ));
+        }
+        html.push_str("</div>\n");
+    }
+
+    // Unprocessed blocks
+    let unprocessed: Vec<&Block> = input_blocks
+        .into_iter()
+        .filter(|block| !output_ids.contains(block.id.as_str()))
+        .collect();
+    html.push_str(&format!("<h2>Unprocessed blocks ({})</h2>\n", unprocessed.len()));
+    if !unprocessed.is_empty() {
+        html.push_str("<table class=\"unprocessed\">\n<tr><th>Block</th><th>Type</th><th>Reason</th></tr>\n");
+        for block in unprocessed {
+            html.push_str(&format!(
+                "<tr><td class=\"id\">{}</td><td>{}</td><td>{}</td></tr>\n",
+                escape_html(&block.id),
+                escape_html(&block.block_type),
+                unprocessed_reason(block, config)
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn unprocessed_reason(block: &Block, config: &ProcessingConfig) -> &'static str {
+    if !config.filter.keeps_type(&block.block_type) {
+        return match block.block_type.as_str() {
+            "PageHeader" | "PageFooter" => "Page header or footer",
+            "Picture" => "Picture (excluded block type)",
+            "ListGroup" => "List group (excluded block type)",
+            _ => "Block type excluded by --exclude-types or --include-only-types",
+        };
+    }
+    match block.block_type.as_str() {
+        "Picture" if is_small_picture(block, config.filter.min_picture_area) => {
+            "Picture smaller than --min-picture-area"
+        }
+        "Footnote" | "Citation" if config.inline_footnotes => "Inlined into the referencing block",
+        "Table" if config.merge_split_tables => "Merged into the preceding table",
+        _ => "Dropped during post-processing",
+    }
+}
+
+// Horizontal bar chart, one bar per block type scaled to the largest count
+fn render_histogram(counts: &BTreeMap<String, usize>) -> String {
+    let max = counts.values().copied().max().unwrap_or(0).max(1);
+    let width = HISTOGRAM_LABEL_WIDTH + HISTOGRAM_BAR_WIDTH + 60;
+    let height = counts.len() * HISTOGRAM_BAR_HEIGHT;
+
+    let mut svg = format!(
+        "<svg class=\"histogram\" xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+        width, height, width, height
+    );
+    for (index, (block_type, &count)) in counts.iter().enumerate() {
+        let y = index * HISTOGRAM_BAR_HEIGHT;
+        let bar_width = (count * HISTOGRAM_BAR_WIDTH / max).max(1);
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>\n",
+            HISTOGRAM_LABEL_WIDTH - 8,
+            y + 15,
+            escape_html(block_type)
+        ));
+        svg.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#4a7ab7\"/>\n",
+            HISTOGRAM_LABEL_WIDTH,
+            y + 3,
+            bar_width,
+            HISTOGRAM_BAR_HEIGHT - 6
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\">{}</text>\n",
+            HISTOGRAM_LABEL_WIDTH + bar_width + 6,
+            y + 15,
+            count
+        ));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn summary_row(html: &mut String, label: &str, value: &str) {
+    html.push_str(&format!(
+        "<tr><th>{}</th><td>{}</td></tr>\n",
+        escape_html(label),
+        escape_html(value)
+    ));
+}
+
+fn format_size(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
@@ -0,0 +1,99 @@
+use crate::Block;
+
+/// Control characters other than newline, carriage return and tab, plus
+/// private-use code points, which OCR output sometimes contains and which
+/// many JSON consumers reject
+/// ```
+/// use pdf_parser::sanitize::is_problematic_char;
+///
+/// assert!(is_problematic_char('\u{7}') && is_problematic_char('\u{e000}'));
+/// assert!(!is_problematic_char('\n') && !is_problematic_char('é'));
+/// ```
+pub fn is_problematic_char(c: char) -> bool {
+    (c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
+        || ('\u{e000}'..='\u{f8ff}').contains(&c)
+        || ('\u{f0000}'..='\u{ffffd}').contains(&c)
+        || ('\u{100000}'..='\u{10fffd}').contains(&c)
+}
+
+/// Replaces control and private-use characters in each block's text with a space
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::sanitize::strip_control_chars;
+///
+/// let mut blocks = [Block { text: "bell\u{7}here".to_string(), ..Default::default() }];
+/// strip_control_chars(&mut blocks);
+/// assert_eq!(blocks[0].text, "bell here");
+/// ```
+pub fn strip_control_chars(blocks: &mut [Block]) {
+    for block in blocks {
+        if block.text.chars().any(is_problematic_char) {
+            block.text = block
+                .text
+                .chars()
+                .map(|c| if is_problematic_char(c) { ' ' } else { c })
+                .collect();
+        }
+    }
+}
+
+/// Drops characters outside the Basic Multilingual Plane (emoji, historic
+/// scripts, ...) from each block's text, for consumers limited to UCS-2
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::sanitize::strip_non_bmp;
+///
+/// let mut blocks = [Block { text: "ok 👍 done".to_string(), ..Default::default() }];
+/// strip_non_bmp(&mut blocks);
+/// assert_eq!(blocks[0].text, "ok  done");
+/// ```
+pub fn strip_non_bmp(blocks: &mut [Block]) {
+    for block in blocks {
+        if block.text.chars().any(|c| c as u32 > 0xffff) {
+            block.text.retain(|c| c as u32 <= 0xffff);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocks(texts: &[&str]) -> Vec<Block> {
+        texts
+            .iter()
+            .map(|text| Block {
+                text: text.to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn texts(blocks: &[Block]) -> Vec<&str> {
+        blocks.iter().map(|block| block.text.as_str()).collect()
+    }
+
+    #[test]
+    fn control_and_private_use_characters() {
+        for c in ['\u{0}', '\u{7}', '\u{1b}', '\u{7f}', '\u{85}', '\u{e000}', '\u{f8ff}', '\u{f0000}', '\u{10fffd}'] {
+            assert!(is_problematic_char(c), "{:?}", c);
+        }
+        for c in ['\n', '\r', '\t', ' ', 'é', '\u{2028}', '\u{fffd}', '😀'] {
+            assert!(!is_problematic_char(c), "{:?}", c);
+        }
+    }
+
+    #[test]
+    fn control_characters_become_spaces() {
+        let mut blocks = blocks(&["Form\u{c}feed and\u{0}nul", "Private\u{e001}use", "Line\nbreaks\tstay"]);
+        strip_control_chars(&mut blocks);
+        assert_eq!(texts(&blocks), ["Form feed and nul", "Private use", "Line\nbreaks\tstay"]);
+    }
+
+    #[test]
+    fn characters_outside_the_bmp_are_dropped() {
+        let mut blocks = blocks(&["Smile 😀 and 𝔸lgebra", "Plain ñ 日本 \u{fffd}"]);
+        strip_non_bmp(&mut blocks);
+        assert_eq!(texts(&blocks), ["Smile  and lgebra", "Plain ñ 日本 \u{fffd}"]);
+    }
+}
@@ -0,0 +1,211 @@
+use serde_json::Value;
+
+// Schema used when no --schema is given: a top-level array of blocks, each
+// carrying the four string fields the flattener always writes
+pub const DEFAULT_BLOCK_SCHEMA: &str = r#"{
+    "type": "array",
+    "items": {
+        "type": "object",
+        "required": ["id", "block_type", "html", "text"],
+        "properties": {
+            "id": { "type": "string" },
+            "block_type": { "type": "string" },
+            "html": { "type": "string" },
+            "text": { "type": "string" }
+        }
+    }
+}"#;
+
+#[derive(Debug)]
+pub struct Violation {
+    pub path: String,
+    pub message: String,
+}
+
+// Validates `instance` against a minimal subset of JSON Schema: `type`,
+// `enum`, `required`, `properties`, `additionalProperties` and `items`.
+// Unknown keywords are ignored. Every violation is collected rather than
+// stopping at the first one so the report is complete.
+pub fn validate(schema: &Value, instance: &Value) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    validate_at(schema, instance, "", &mut violations);
+    violations
+}
+
+fn validate_at(schema: &Value, instance: &Value, path: &str, violations: &mut Vec<Violation>) {
+    // A boolean schema either accepts or rejects everything
+    let schema = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => {
+            violations.push(Violation {
+                path: display_path(path),
+                message: "value is not allowed here".to_string(),
+            });
+            return;
+        }
+        Value::Object(schema) => schema,
+        _ => return,
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let allowed: Vec<&str> = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(|name| name.as_str()).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|name| matches_type(name, instance)) {
+            violations.push(Violation {
+                path: display_path(path),
+                message: format!(
+                    "expected {}, found {}",
+                    allowed.join(" or "),
+                    type_name(instance)
+                ),
+            });
+            // Checking nested keywords against the wrong type only adds noise
+            return;
+        }
+    }
+
+    if let Some(Value::Array(options)) = schema.get("enum")
+        && !options.contains(instance)
+    {
+        violations.push(Violation {
+            path: display_path(path),
+            message: format!("value {} is not one of the allowed values", instance),
+        });
+    }
+
+    if let Value::Object(fields) = instance {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for name in required.iter().filter_map(|name| name.as_str()) {
+                if !fields.contains_key(name) {
+                    violations.push(Violation {
+                        path: display_path(path),
+                        message: format!("missing required field '{}'", name),
+                    });
+                }
+            }
+        }
+
+        let properties = schema.get("properties").and_then(|p| p.as_object());
+        for (name, value) in fields {
+            let field_path = format!("{}/{}", path, name);
+            match properties.and_then(|p| p.get(name)) {
+                Some(field_schema) => validate_at(field_schema, value, &field_path, violations),
+                None => {
+                    if let Some(additional) = schema.get("additionalProperties") {
+                        validate_at(additional, value, &field_path, violations);
+                    }
+                }
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(item_schema)) = (instance, schema.get("items")) {
+        for (index, item) in items.iter().enumerate() {
+            validate_at(item_schema, item, &format!("{}/{}", path, index), violations);
+        }
+    }
+}
+
+fn matches_type(name: &str, instance: &Value) -> bool {
+    match name {
+        "null" => instance.is_null(),
+        "boolean" => instance.is_boolean(),
+        "object" => instance.is_object(),
+        "array" => instance.is_array(),
+        "string" => instance.is_string(),
+        "number" => instance.is_number(),
+        "integer" => instance.is_i64() || instance.is_u64(),
+        _ => false,
+    }
+}
+
+fn type_name(instance: &Value) -> &'static str {
+    match instance {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn display_path(path: &str) -> String {
+    if path.is_empty() {
+        "/".to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn default_schema() -> Value {
+        serde_json::from_str(DEFAULT_BLOCK_SCHEMA).unwrap()
+    }
+
+    fn messages(violations: &[Violation]) -> Vec<String> {
+        violations
+            .iter()
+            .map(|violation| format!("{}: {}", violation.path, violation.message))
+            .collect()
+    }
+
+    #[test]
+    fn processed_blocks_pass_the_default_schema() {
+        let blocks = json!([
+            { "id": "/page/0/Text/1", "block_type": "Text", "html": "<p>a</p>", "text": "a" },
+            { "id": "/page/0/Table/2", "block_type": "Table", "html": "", "text": "", "table_csv_path": "t.csv" }
+        ]);
+        assert!(validate(&default_schema(), &blocks).is_empty());
+    }
+
+    #[test]
+    fn wrong_types_and_missing_fields_are_all_reported() {
+        let blocks = json!([{ "id": 1, "block_type": "Text", "html": "" }, "not a block"]);
+        assert_eq!(
+            messages(&validate(&default_schema(), &blocks)),
+            vec![
+                "/0: missing required field 'text'",
+                "/0/id: expected string, found number",
+                "/1: expected object, found string",
+            ]
+        );
+    }
+
+    #[test]
+    fn top_level_must_be_an_array() {
+        let violations = validate(&default_schema(), &json!({ "children": [] }));
+        assert_eq!(messages(&violations), vec!["/: expected array, found object"]);
+    }
+
+    #[test]
+    fn enum_and_additional_properties() {
+        let schema = json!({
+            "properties": { "block_type": { "enum": ["Text"] } },
+            "additionalProperties": false
+        });
+        let violations = validate(&schema, &json!({ "block_type": "Picture", "extra": 1 }));
+        assert_eq!(
+            messages(&violations),
+            vec![
+                "/block_type: value \"Picture\" is not one of the allowed values",
+                "/extra: value is not allowed here",
+            ]
+        );
+    }
+
+    #[test]
+    fn integer_type_rejects_fractions() {
+        let schema = json!({ "type": ["integer", "null"] });
+        assert!(validate(&schema, &json!(3)).is_empty());
+        assert!(validate(&schema, &json!(null)).is_empty());
+        assert_eq!(messages(&validate(&schema, &json!(1.5))), vec!["/: expected integer or null, found number"]);
+    }
+}
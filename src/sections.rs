@@ -0,0 +1,121 @@
+use crate::Block;
+use serde::Serialize;
+
+// Header text of the synthetic section holding content before the first heading
+pub const PREAMBLE_HEADER: &str = "__PREAMBLE__";
+
+#[derive(Serialize, Debug)]
+pub struct SectionGroup {
+    pub header: Block,
+    pub body: Vec<Block>,
+}
+
+/// Groups blocks into sections, each starting at a `SectionHeader` and holding
+/// every block up to the next one. Blocks before the first heading go into a
+/// synthetic `__PREAMBLE__` section.
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::sections::group_blocks_by_section;
+///
+/// let block = |block_type: &str, text: &str| Block {
+///     block_type: block_type.to_string(),
+///     text: text.to_string(),
+///     ..Default::default()
+/// };
+/// let sections = group_blocks_by_section(vec![
+///     block("Text", "Foreword"),
+///     block("SectionHeader", "Methods"),
+///     block("Text", "We measured."),
+/// ]);
+/// assert_eq!(sections[0].header.text, "__PREAMBLE__");
+/// assert_eq!(sections[1].header.text, "Methods");
+/// assert_eq!(sections[1].body.len(), 1);
+/// ```
+pub fn group_blocks_by_section(blocks: Vec<Block>) -> Vec<SectionGroup> {
+    let mut groups: Vec<SectionGroup> = Vec::new();
+
+    for block in blocks {
+        if block.block_type == "SectionHeader" {
+            groups.push(SectionGroup {
+                header: block,
+                body: Vec::new(),
+            });
+            continue;
+        }
+
+        match groups.last_mut() {
+            Some(group) => group.body.push(block),
+            None => groups.push(SectionGroup {
+                header: Block {
+                    block_type: "SectionHeader".to_string(),
+                    text: PREAMBLE_HEADER.to_string(),
+                    ..Default::default()
+                },
+                body: vec![block],
+            }),
+        }
+    }
+
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(block_type: &str, text: &str) -> Block {
+        Block {
+            block_type: block_type.to_string(),
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn texts(blocks: &[Block]) -> Vec<&str> {
+        blocks.iter().map(|block| block.text.as_str()).collect()
+    }
+
+    #[test]
+    fn blocks_are_grouped_under_the_preceding_header() {
+        let groups = group_blocks_by_section(vec![
+            block("SectionHeader", "Introduction"),
+            block("Text", "First paragraph"),
+            block("ListGroup", "• Item"),
+            block("SectionHeader", "Methods"),
+            block("Text", "Second paragraph"),
+        ]);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].header.text, "Introduction");
+        assert_eq!(texts(&groups[0].body), ["First paragraph", "• Item"]);
+        assert_eq!(groups[1].header.text, "Methods");
+        assert_eq!(texts(&groups[1].body), ["Second paragraph"]);
+    }
+
+    #[test]
+    fn content_before_the_first_header_goes_into_a_preamble() {
+        let groups = group_blocks_by_section(vec![
+            block("PageHeader", "Running title"),
+            block("Text", "Abstract"),
+            block("SectionHeader", "Introduction"),
+        ]);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].header.block_type, "SectionHeader");
+        assert_eq!(groups[0].header.text, PREAMBLE_HEADER);
+        assert_eq!(texts(&groups[0].body), ["Running title", "Abstract"]);
+        assert!(groups[1].body.is_empty());
+    }
+
+    #[test]
+    fn consecutive_headers_and_empty_input() {
+        let groups = group_blocks_by_section(vec![
+            block("SectionHeader", "Part I"),
+            block("SectionHeader", "Chapter 1"),
+        ]);
+        assert_eq!(groups.len(), 2);
+        assert!(groups.iter().all(|group| group.body.is_empty()));
+
+        assert!(group_blocks_by_section(Vec::new()).is_empty());
+    }
+}
@@ -0,0 +1,198 @@
+// Machine-readable counts describing what processing did to one file
+use crate::Block;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ProcessingStats {
+    pub input_file: String,
+    pub total_blocks_before: usize,
+    pub total_blocks_after: usize,
+    pub blocks_by_type_before: BTreeMap<String, usize>,
+    pub blocks_by_type_after: BTreeMap<String, usize>,
+    // Types with fewer blocks after processing than before, including
+    // containers such as Page that flattening always removes
+    pub filtered_block_types: Vec<String>,
+    pub processing_time_ms: u64,
+}
+
+impl ProcessingStats {
+    /// `before` comes from count_blocks_by_type on the Marker blocks, taken
+    /// before processing consumes them
+    /// ```
+    /// use pdf_parser::stats::{ProcessingStats, count_blocks_by_type};
+    /// use pdf_parser::{Block, FilterConfig, flatten_and_filter_blocks};
+    /// use std::time::Duration;
+    ///
+    /// let block = |block_type: &str| Block { block_type: block_type.to_string(), ..Default::default() };
+    /// let page = Block {
+    ///     children: Some(vec![block("PageHeader"), block("Text")]),
+    ///     ..block("Page")
+    /// };
+    /// let before = count_blocks_by_type(std::slice::from_ref(&page));
+    /// let processed = flatten_and_filter_blocks(vec![page], &FilterConfig::default());
+    ///
+    /// let stats = ProcessingStats::new("doc.json", before, &processed, Duration::from_millis(12));
+    /// assert_eq!((stats.total_blocks_before, stats.total_blocks_after), (3, 1));
+    /// assert_eq!(stats.filtered_block_types, ["Page", "PageHeader"]);
+    /// ```
+    pub fn new(
+        input_file: &str,
+        before: BTreeMap<String, usize>,
+        processed: &[Block],
+        elapsed: Duration,
+    ) -> Self {
+        Self::from_counts(input_file, before, count_blocks_by_type(processed), elapsed)
+    }
+
+    /// Like new, from block counts taken before and after processing. Used
+    /// when the processed blocks are written out as they are produced and
+    /// never collected, so `after` is counted as they go.
+    /// ```
+    /// use pdf_parser::stats::ProcessingStats;
+    /// use std::collections::BTreeMap;
+    /// use std::time::Duration;
+    ///
+    /// let before = BTreeMap::from([("Page".to_string(), 2), ("Text".to_string(), 5)]);
+    /// let after = BTreeMap::from([("Text".to_string(), 5)]);
+    /// let stats = ProcessingStats::from_counts("doc.json", before, after, Duration::ZERO);
+    /// assert_eq!((stats.total_blocks_before, stats.total_blocks_after), (7, 5));
+    /// assert_eq!(stats.filtered_block_types, ["Page"]);
+    /// ```
+    pub fn from_counts(
+        input_file: &str,
+        before: BTreeMap<String, usize>,
+        after: BTreeMap<String, usize>,
+        elapsed: Duration,
+    ) -> Self {
+        let filtered_block_types = before
+            .iter()
+            .filter(|(block_type, count)| after.get(*block_type).copied().unwrap_or(0) < **count)
+            .map(|(block_type, _)| block_type.clone())
+            .collect();
+
+        ProcessingStats {
+            input_file: input_file.to_string(),
+            total_blocks_before: before.values().sum(),
+            total_blocks_after: after.values().sum(),
+            blocks_by_type_before: before,
+            blocks_by_type_after: after,
+            filtered_block_types,
+            processing_time_ms: elapsed.as_millis() as u64,
+        }
+    }
+}
+
+/// Number of blocks of each type in the whole tree, counting every nested
+/// child (the blocks inside Page containers, ListItems inside ListGroups, ...)
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::stats::count_blocks_by_type;
+///
+/// let block = |block_type: &str| Block { block_type: block_type.to_string(), ..Default::default() };
+/// let list = Block { children: Some(vec![block("ListItem"), block("ListItem")]), ..block("ListGroup") };
+/// let page = Block { children: Some(vec![list]), ..block("Page") };
+///
+/// let counts = count_blocks_by_type(&[page]);
+/// assert_eq!((counts["Page"], counts["ListGroup"], counts["ListItem"]), (1, 1, 2));
+/// ```
+pub fn count_blocks_by_type(blocks: &[Block]) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    add_block_counts(blocks, &mut counts);
+    counts
+}
+
+fn add_block_counts(blocks: &[Block], counts: &mut BTreeMap<String, usize>) {
+    for block in blocks {
+        *counts.entry(block.block_type.clone()).or_insert(0) += 1;
+        if let Some(children) = &block.children {
+            add_block_counts(children, counts);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Document, FilterConfig, flatten_and_filter_blocks};
+
+    fn block(block_type: &str, children: Option<Vec<Block>>) -> Block {
+        Block {
+            block_type: block_type.to_string(),
+            html: format!("<p>{}</p>", block_type),
+            children,
+            ..Default::default()
+        }
+    }
+
+    // Page → [PageHeader, Text, ListGroup → [ListItem, ListItem]], Page → [Text]
+    fn document() -> Document {
+        let list = block("ListGroup", Some(vec![block("ListItem", None), block("ListItem", None)]));
+        Document {
+            children: vec![
+                block("Page", Some(vec![block("PageHeader", None), block("Text", None), list])),
+                block("Page", Some(vec![block("Text", None)])),
+            ],
+        }
+    }
+
+    fn counts(pairs: &[(&str, usize)]) -> BTreeMap<String, usize> {
+        pairs.iter().map(|(block_type, count)| (block_type.to_string(), *count)).collect()
+    }
+
+    #[test]
+    fn nested_children_are_counted() {
+        assert_eq!(
+            count_blocks_by_type(&document().children),
+            counts(&[("ListGroup", 1), ("ListItem", 2), ("Page", 2), ("PageHeader", 1), ("Text", 2)])
+        );
+    }
+
+    #[test]
+    fn empty_and_childless_blocks() {
+        assert!(count_blocks_by_type(&[]).is_empty());
+        let empty_page = block("Page", Some(Vec::new()));
+        assert_eq!(count_blocks_by_type(&[empty_page]), counts(&[("Page", 1)]));
+    }
+
+    #[test]
+    fn stats_compare_the_tree_with_the_flattened_blocks() {
+        let document = document();
+        let before = count_blocks_by_type(&document.children);
+        let processed = flatten_and_filter_blocks(document.children, &FilterConfig::default());
+
+        let stats = ProcessingStats::new("doc.json", before, &processed, Duration::from_micros(2500));
+        assert_eq!(stats.input_file, "doc.json");
+        assert_eq!(stats.total_blocks_before, 8);
+        assert_eq!(stats.total_blocks_after, 2);
+        assert_eq!(stats.blocks_by_type_after, counts(&[("Text", 2)]));
+        // ListGroup is excluded by default, which drops its ListItems too
+        assert_eq!(stats.filtered_block_types, ["ListGroup", "ListItem", "Page", "PageHeader"]);
+        assert_eq!(stats.processing_time_ms, 2);
+    }
+
+    #[test]
+    fn kept_list_groups_are_counted_once_with_their_items_inlined() {
+        let document = document();
+        let before = count_blocks_by_type(&document.children);
+        let filter = FilterConfig {
+            excluded_types: vec!["PageHeader".to_string()],
+            ..Default::default()
+        };
+        let processed = flatten_and_filter_blocks(document.children, &filter);
+
+        let stats = ProcessingStats::new("doc.json", before, &processed, Duration::ZERO);
+        assert_eq!(stats.blocks_by_type_after, counts(&[("ListGroup", 1), ("Text", 2)]));
+        assert_eq!(stats.filtered_block_types, ["ListItem", "Page", "PageHeader"]);
+    }
+
+    #[test]
+    fn types_kept_in_full_are_not_filtered() {
+        let blocks = [block("Text", None), block("Table", None)];
+        let before = count_blocks_by_type(&blocks);
+        let stats = ProcessingStats::new("doc.json", before.clone(), &blocks, Duration::ZERO);
+        assert_eq!(stats.blocks_by_type_after, before);
+        assert!(stats.filtered_block_types.is_empty());
+    }
+}
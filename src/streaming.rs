@@ -0,0 +1,173 @@
+// Processed output written one block at a time, for documents that are
+// processed page by page and never held in memory whole. The bytes match
+// what the whole-document writers produce for the same blocks.
+use crate::{Block, OutputFormat};
+use std::io::{self, Write};
+
+pub struct BlockWriter<W: Write> {
+    format: OutputFormat,
+    writer: W,
+    blocks_written: usize,
+}
+
+/// Whether output in `format` can be written a block at a time. Markdown
+/// output groups list items and tables by their neighbours, and TOML and
+/// Parquet are serialized whole.
+/// ```
+/// use pdf_parser::OutputFormat;
+/// use pdf_parser::streaming::supports;
+///
+/// assert!(supports(OutputFormat::Json) && supports(OutputFormat::Text));
+/// assert!(!supports(OutputFormat::Markdown));
+/// ```
+pub fn supports(format: OutputFormat) -> bool {
+    matches!(format, OutputFormat::Json | OutputFormat::Text)
+}
+
+impl<W: Write> BlockWriter<W> {
+    /// Starts writing `format` output to `writer`, or None if the format
+    /// can't be streamed (see supports)
+    /// ```
+    /// use pdf_parser::{Block, OutputFormat};
+    /// use pdf_parser::streaming::BlockWriter;
+    ///
+    /// let mut writer = BlockWriter::new(OutputFormat::Text, Vec::new()).unwrap();
+    /// writer.write_block(&Block { text: "First".to_string(), ..Default::default() })?;
+    /// writer.write_block(&Block { text: "Second".to_string(), ..Default::default() })?;
+    /// assert_eq!(writer.finish()?, b"First\nSecond\n");
+    ///
+    /// assert!(BlockWriter::new(OutputFormat::Toml, Vec::new()).is_none());
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn new(format: OutputFormat, writer: W) -> Option<Self> {
+        supports(format).then_some(BlockWriter {
+            format,
+            writer,
+            blocks_written: 0,
+        })
+    }
+
+    /// Appends one block to the output
+    /// ```
+    /// use pdf_parser::{Block, OutputFormat};
+    /// use pdf_parser::streaming::BlockWriter;
+    ///
+    /// let block = Block { id: "/page/0/Text/0".to_string(), block_type: "Text".to_string(), ..Default::default() };
+    /// let mut writer = BlockWriter::new(OutputFormat::Json, Vec::new()).unwrap();
+    /// writer.write_block(&block)?;
+    /// assert_eq!(writer.finish()?, serde_json::to_string_pretty(&[block])?.into_bytes());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn write_block(&mut self, block: &Block) -> io::Result<()> {
+        match self.format {
+            OutputFormat::Json => {
+                // A block inside serde_json's pretty-printed array is its own
+                // pretty-printed form indented one level. Strings never
+                // contain raw newlines, so every line is indented.
+                let json = serde_json::to_string_pretty(block)?;
+                let separator: &[u8] = if self.blocks_written == 0 { b"[\n" } else { b",\n" };
+                self.writer.write_all(separator)?;
+                for (index, line) in json.lines().enumerate() {
+                    if index > 0 {
+                        self.writer.write_all(b"\n")?;
+                    }
+                    self.writer.write_all(b"  ")?;
+                    self.writer.write_all(line.as_bytes())?;
+                }
+            }
+            _ => {
+                self.writer.write_all(block.text.as_bytes())?;
+                self.writer.write_all(b"\n")?;
+            }
+        }
+        self.blocks_written += 1;
+        Ok(())
+    }
+
+    /// Closes the output and returns the underlying writer, flushed
+    /// ```
+    /// use pdf_parser::OutputFormat;
+    /// use pdf_parser::streaming::BlockWriter;
+    ///
+    /// // An empty document is an empty array, or an empty line of text
+    /// assert_eq!(BlockWriter::new(OutputFormat::Json, Vec::new()).unwrap().finish()?, b"[]");
+    /// assert_eq!(BlockWriter::new(OutputFormat::Text, Vec::new()).unwrap().finish()?, b"\n");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn finish(mut self) -> io::Result<W> {
+        let end: &[u8] = match (self.format, self.blocks_written) {
+            (OutputFormat::Json, 0) => b"[]",
+            (OutputFormat::Json, _) => b"\n]",
+            (_, 0) => b"\n",
+            _ => b"",
+        };
+        self.writer.write_all(end)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocks() -> Vec<Block> {
+        vec![
+            Block {
+                id: "/page/0/SectionHeader/0".to_string(),
+                block_type: "SectionHeader".to_string(),
+                html: "<h1>Año \"uno\"</h1>".to_string(),
+                text: "Año \"uno\"".to_string(),
+                ..Default::default()
+            },
+            Block {
+                id: "/page/0/Table/1".to_string(),
+                block_type: "Table".to_string(),
+                html: "<table>\n<tr><td>a\tb</td></tr>\n</table>".to_string(),
+                text: "a\tb".to_string(),
+                table_csv_path: Some("doc_table_0.csv".to_string()),
+                ..Default::default()
+            },
+            Block {
+                id: "/page/1/Text/0".to_string(),
+                block_type: "Text".to_string(),
+                text: "Line one\nline two".to_string(),
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn stream(format: OutputFormat, blocks: &[Block]) -> String {
+        let mut writer = BlockWriter::new(format, Vec::new()).unwrap();
+        for block in blocks {
+            writer.write_block(block).unwrap();
+        }
+        String::from_utf8(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn json_matches_the_pretty_printed_array() {
+        let blocks = blocks();
+        for count in 0..=blocks.len() {
+            let expected = serde_json::to_string_pretty(&blocks[..count]).unwrap();
+            assert_eq!(stream(OutputFormat::Json, &blocks[..count]), expected, "{} blocks", count);
+        }
+    }
+
+    #[test]
+    fn text_matches_the_joined_lines() {
+        let blocks = blocks();
+        for count in 0..=blocks.len() {
+            let texts: Vec<&str> = blocks[..count].iter().map(|block| block.text.as_str()).collect();
+            assert_eq!(stream(OutputFormat::Text, &blocks[..count]), texts.join("\n") + "\n");
+        }
+    }
+
+    #[test]
+    fn whole_document_formats_are_not_streamed() {
+        for format in [OutputFormat::Markdown, OutputFormat::Toml, OutputFormat::Parquet] {
+            assert!(!supports(format));
+            assert!(BlockWriter::new(format, Vec::new()).is_none());
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use crate::Block;
+use crate::tokenize::tokenize;
+use regex::Regex;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+// Block types whose text is prose worth summarizing
+const SUMMARY_BLOCK_TYPES: [&str; 2] = ["Text", "ListItem"];
+
+// Sentences with fewer content words than this (verse references, fragments
+// left by line breaks) are never selected
+const MIN_SENTENCE_TOKENS: usize = 5;
+
+#[derive(Debug, Clone)]
+pub struct SlidingWindow {
+    pub size: usize,
+    pub overlap: usize,
+    pub sentences_per_window: usize,
+}
+
+/// Splits text into sentences at `.`, `!`, `?` or `…` followed by whitespace
+/// ```
+/// use pdf_parser::summarize::split_sentences;
+///
+/// assert_eq!(split_sentences("It rained. Did it stop? No… Not yet"), ["It rained.", "Did it stop?", "No…", "Not yet"]);
+/// ```
+pub fn split_sentences(text: &str) -> Vec<String> {
+    let re = Regex::new(r"[.!?…]+[\s]+").unwrap();
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    for boundary in re.find_iter(text) {
+        sentences.push(text[start..boundary.end()].trim().to_string());
+        start = boundary.end();
+    }
+    sentences.push(text[start..].trim().to_string());
+    sentences.retain(|sentence| !sentence.is_empty());
+    sentences
+}
+
+/// Extractive summary: scores each sentence by the mean TF-IDF weight of its
+/// tokens (treating every sentence as a document for IDF) and returns the
+/// indices of the `count` highest-scoring sentences in their original order.
+/// Sentences shorter than MIN_SENTENCE_TOKENS are skipped.
+/// ```
+/// use pdf_parser::summarize::summarize;
+///
+/// let sentences = [
+///     "The committee approved the annual budget for public libraries.",
+///     "The committee approved the annual budget.",
+///     "Volcanic eruptions reshaped the northern coastline dramatically overnight.",
+///     "Short one.",
+/// ];
+/// let picked = summarize(&sentences, 2);
+/// assert_eq!(picked.len(), 2);
+/// assert!(picked.contains(&2) && !picked.contains(&3));
+/// ```
+pub fn summarize(sentences: &[&str], count: usize) -> Vec<usize> {
+    let tokenized: Vec<Vec<String>> = sentences.iter().map(|sentence| tokenize(sentence)).collect();
+
+    let mut document_frequency: HashMap<&str, usize> = HashMap::new();
+    for tokens in &tokenized {
+        let unique: HashSet<&str> = tokens.iter().map(String::as_str).collect();
+        for token in unique {
+            *document_frequency.entry(token).or_insert(0) += 1;
+        }
+    }
+
+    let total = sentences.len() as f64;
+    let mut scored: Vec<(usize, f64)> = tokenized
+        .iter()
+        .enumerate()
+        .map(|(index, tokens)| {
+            if tokens.len() < MIN_SENTENCE_TOKENS {
+                return (index, 0.0);
+            }
+            let mut term_frequency: HashMap<&str, usize> = HashMap::new();
+            for token in tokens {
+                *term_frequency.entry(token.as_str()).or_insert(0) += 1;
+            }
+            let score: f64 = term_frequency
+                .iter()
+                .map(|(token, &count)| {
+                    let idf = (total / document_frequency[token] as f64).ln() + 1.0;
+                    count as f64 * idf
+                })
+                .sum();
+            (index, score / tokens.len() as f64)
+        })
+        .collect();
+
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    let mut selected: Vec<usize> = scored
+        .into_iter()
+        .filter(|&(_, score)| score > 0.0)
+        .take(count)
+        .map(|(index, _)| index)
+        .collect();
+    selected.sort_unstable();
+    selected
+}
+
+/// Summarizes long documents window by window: the prose blocks are split
+/// into overlapping windows of `window.size` blocks, the extractive summarizer
+/// picks `window.sentences_per_window` sentences from each, and the union of
+/// the picks is returned in document order. Scoring each window separately
+/// keeps terms that dominate the whole document from crowding out sections
+/// with their own vocabulary.
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::summarize::{SlidingWindow, sliding_window_summary};
+///
+/// let text = |text: &str| Block { block_type: "Text".to_string(), text: text.to_string(), ..Default::default() };
+/// let blocks = [
+///     text("Rivers carry sediment from distant mountains toward the sea."),
+///     text("Glaciers carve deep valleys through ancient granite ridges."),
+/// ];
+/// let window = SlidingWindow { size: 1, overlap: 0, sentences_per_window: 1 };
+/// assert_eq!(sliding_window_summary(&blocks, &window).len(), 2);
+/// ```
+pub fn sliding_window_summary(blocks: &[Block], window: &SlidingWindow) -> Vec<String> {
+    let block_sentences: Vec<Vec<String>> = blocks
+        .iter()
+        .filter(|block| SUMMARY_BLOCK_TYPES.contains(&block.block_type.as_str()))
+        .map(|block| split_sentences(&block.text))
+        .collect();
+
+    let size = window.size.max(1);
+    let stride = size.saturating_sub(window.overlap).max(1);
+
+    // (block index, sentence index) pairs, which order sentences in the document
+    let mut selected: BTreeSet<(usize, usize)> = BTreeSet::new();
+    let mut start = 0;
+    while start < block_sentences.len() {
+        let end = (start + size).min(block_sentences.len());
+
+        let mut positions = Vec::new();
+        let mut sentences = Vec::new();
+        for (block_index, block) in block_sentences.iter().enumerate().take(end).skip(start) {
+            for (sentence_index, sentence) in block.iter().enumerate() {
+                positions.push((block_index, sentence_index));
+                sentences.push(sentence.as_str());
+            }
+        }
+
+        for index in summarize(&sentences, window.sentences_per_window) {
+            selected.insert(positions[index]);
+        }
+
+        if end == block_sentences.len() {
+            break;
+        }
+        start += stride;
+    }
+
+    selected
+        .into_iter()
+        .map(|(block_index, sentence_index)| block_sentences[block_index][sentence_index].clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(block_type: &str, text: &str) -> Block {
+        Block {
+            block_type: block_type.to_string(),
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    const RIVERS: &str = "Rivers carry sediment downstream toward wide deltas.";
+    const GLACIERS: &str = "Glaciers carve granite valleys during long winters.";
+    const BASALT: &str = "Volcanic basalt columns crystallize slowly under cooling lava.";
+    const CORAL: &str = "Coral reefs shelter colorful tropical fish species.";
+
+    #[test]
+    fn sentences_split_at_terminal_punctuation_and_whitespace() {
+        assert_eq!(
+            split_sentences("It costs 3.50 today. Really?! Yes… it does\nand more"),
+            ["It costs 3.50 today.", "Really?!", "Yes…", "it does\nand more"]
+        );
+        assert!(split_sentences("  ").is_empty());
+    }
+
+    #[test]
+    fn distinctive_sentences_score_highest() {
+        let sentences = [RIVERS, RIVERS, "Too short to count.", GLACIERS];
+        assert_eq!(summarize(&sentences, 1), [3]);
+        assert_eq!(summarize(&sentences, 2), [0, 3]);
+    }
+
+    #[test]
+    fn short_sentences_are_never_selected() {
+        let sentences = [RIVERS, "Too short to count.", GLACIERS];
+        assert_eq!(summarize(&sentences, 10), [0, 2]);
+        assert!(summarize(&[], 3).is_empty());
+    }
+
+    #[test]
+    fn each_window_contributes_its_own_sentences() {
+        let blocks = [
+            block("Text", RIVERS),
+            block("Text", &format!("{} {}", RIVERS, GLACIERS)),
+            block("SectionHeader", "Volcanoes and reefs of the southern islands"),
+            block("Text", BASALT),
+            block("ListItem", &format!("{} {}", BASALT, CORAL)),
+        ];
+        let window = SlidingWindow {
+            size: 2,
+            overlap: 0,
+            sentences_per_window: 1,
+        };
+        assert_eq!(sliding_window_summary(&blocks, &window), [GLACIERS, CORAL]);
+    }
+
+    #[test]
+    fn overlap_at_least_the_window_size_still_advances() {
+        let blocks = [block("Text", GLACIERS), block("Text", CORAL)];
+        let window = SlidingWindow {
+            size: 1,
+            overlap: 5,
+            sentences_per_window: 1,
+        };
+        assert_eq!(sliding_window_summary(&blocks, &window), [GLACIERS, CORAL]);
+    }
+}
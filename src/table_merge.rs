@@ -0,0 +1,151 @@
+use crate::tables::parse_table_rows;
+use crate::{Block, extract_text_from_html};
+use regex::Regex;
+
+/// Merges each Table block into the Table block right before it when both
+/// start with the same header row, which is how Marker leaves a table that
+/// was split by a page or column break. The second table's data rows are
+/// appended to the first and its repeated header is dropped. Returns the
+/// blocks and the number of merges.
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::table_merge::merge_split_tables;
+///
+/// let table = |html: &str| Block { block_type: "Table".to_string(), html: html.to_string(), ..Default::default() };
+/// let (blocks, merged) = merge_split_tables(vec![
+///     table("<table><tr><th>Name</th></tr><tr><td>A</td></tr></table>"),
+///     table("<table><tr><th>Name</th></tr><tr><td>B</td></tr></table>"),
+/// ]);
+/// assert_eq!(merged, 1);
+/// assert_eq!(blocks[0].html, "<table><tr><th>Name</th></tr><tr><td>A</td></tr><tr><td>B</td></tr></table>");
+/// assert_eq!(blocks[0].text, "Name A B");
+/// ```
+pub fn merge_split_tables(blocks: Vec<Block>) -> (Vec<Block>, usize) {
+    let mut result: Vec<Block> = Vec::with_capacity(blocks.len());
+    let mut merged = 0;
+
+    for block in blocks {
+        if block.block_type == "Table"
+            && let Some(previous) = result.last_mut()
+            && previous.block_type == "Table"
+            && let Some(html) = merge_table_html(&previous.html, &block.html)
+        {
+            previous.text = extract_text_from_html(&html);
+            previous.html = html;
+            merged += 1;
+            continue;
+        }
+        result.push(block);
+    }
+
+    (result, merged)
+}
+
+// The first table's HTML with the second table's rows after its header
+// inserted at the end of its body, or None if the header rows differ
+fn merge_table_html(first: &str, second: &str) -> Option<String> {
+    let first_rows = parse_table_rows(first);
+    let second_rows = parse_table_rows(second);
+    let first_header = normalize_row(first_rows.first()?);
+    if first_header != normalize_row(second_rows.first()?) {
+        return None;
+    }
+
+    // Rows of the second table as HTML, skipping rows without cells like
+    // parse_table_rows does so the header is the first one skipped
+    let row_re = Regex::new(r"(?is)<tr[^>]*>.*?</tr>").unwrap();
+    let cell_re = Regex::new(r"(?is)<t[hd][^>]*>").unwrap();
+    let data_rows: String = row_re
+        .find_iter(second)
+        .filter(|row| cell_re.is_match(row.as_str()))
+        .skip(1)
+        .map(|row| row.as_str())
+        .collect();
+
+    let lowercase = first.to_ascii_lowercase();
+    let insert_at = lowercase
+        .rfind("</tbody>")
+        .or_else(|| lowercase.rfind("</table>"))
+        .unwrap_or(first.len());
+
+    let mut html = String::with_capacity(first.len() + data_rows.len());
+    html.push_str(&first[..insert_at]);
+    html.push_str(&data_rows);
+    html.push_str(&first[insert_at..]);
+    Some(html)
+}
+
+fn normalize_row(row: &[String]) -> Vec<String> {
+    row.iter()
+        .map(|cell| cell.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(block_type: &str, html: &str) -> Block {
+        Block {
+            block_type: block_type.to_string(),
+            html: html.to_string(),
+            text: extract_text_from_html(html),
+            ..Default::default()
+        }
+    }
+
+    const FIRST_HALF: &str =
+        "<table><thead><tr><th>Name</th><th>Year</th></tr></thead><tbody><tr><td>A</td><td>1</td></tr></tbody></table>";
+    const SECOND_HALF: &str =
+        "<table><tr><th> name </th><th>YEAR</th></tr><tr><td>B</td><td>2</td></tr><tr><td>C</td><td>3</td></tr></table>";
+
+    #[test]
+    fn halves_with_the_same_header_are_merged() {
+        let (blocks, merged) = merge_split_tables(vec![block("Table", FIRST_HALF), block("Table", SECOND_HALF)]);
+        assert_eq!(merged, 1);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(
+            blocks[0].html,
+            "<table><thead><tr><th>Name</th><th>Year</th></tr></thead><tbody><tr><td>A</td><td>1</td></tr>\
+             <tr><td>B</td><td>2</td></tr><tr><td>C</td><td>3</td></tr></tbody></table>"
+        );
+        assert_eq!(blocks[0].text, extract_text_from_html(&blocks[0].html));
+        assert_eq!(
+            parse_table_rows(&blocks[0].html),
+            [["Name", "Year"], ["A", "1"], ["B", "2"], ["C", "3"]]
+        );
+    }
+
+    #[test]
+    fn tables_split_three_ways_merge_into_one() {
+        let third = "<table><tr><th>Name</th><th>Year</th></tr><tr><td>D</td><td>4</td></tr></table>";
+        let (blocks, merged) = merge_split_tables(vec![
+            block("Table", FIRST_HALF),
+            block("Table", SECOND_HALF),
+            block("Table", third),
+        ]);
+        assert_eq!(merged, 2);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(parse_table_rows(&blocks[0].html).len(), 5);
+    }
+
+    #[test]
+    fn different_headers_are_left_apart() {
+        let other = "<table><tr><th>Name</th><th>Place</th></tr><tr><td>B</td><td>X</td></tr></table>";
+        let (blocks, merged) = merge_split_tables(vec![block("Table", FIRST_HALF), block("Table", other)]);
+        assert_eq!(merged, 0);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[1].html, other);
+    }
+
+    #[test]
+    fn only_adjacent_tables_are_merged() {
+        let (blocks, merged) = merge_split_tables(vec![
+            block("Table", FIRST_HALF),
+            block("Text", "<p>Between</p>"),
+            block("Table", SECOND_HALF),
+        ]);
+        assert_eq!(merged, 0);
+        assert_eq!(blocks.len(), 3);
+    }
+}
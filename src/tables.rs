@@ -0,0 +1,393 @@
+use crate::extract_text_from_html;
+use regex::Regex;
+use std::fmt;
+
+// First-column width (in characters) up to which a two-column table reads
+// better as `term: value` pairs than as a grid
+const MAX_DEFINITION_TERM_WIDTH: usize = 40;
+
+// Column count above which pipe tables become unreadable in plain text
+const MAX_GFM_COLUMNS: usize = 6;
+
+// Widest colspan accepted by parse_html_table; anything larger is a
+// malformed attribute rather than a real table
+const MAX_COLSPAN: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TableParseError {
+    // The HTML has no <tr> rows with cells in them
+    NoRows,
+    // A cell's colspan attribute is not a number from 1 to MAX_COLSPAN
+    InvalidColspan(String),
+}
+
+impl fmt::Display for TableParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableParseError::NoRows => write!(f, "table has no rows"),
+            TableParseError::InvalidColspan(value) => write!(f, "invalid colspan {:?}", value),
+        }
+    }
+}
+
+impl std::error::Error for TableParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TableFormat {
+    DefinitionList,
+    Gfm,
+    Ascii,
+}
+
+/// Picks the rendering that suits a table's shape: definition lists for
+/// two-column key/value tables, pipe tables for moderate widths and ASCII
+/// grids once there are too many columns for a pipe table to stay legible
+/// ```
+/// use pdf_parser::tables::{TableFormat, choose_table_format};
+///
+/// assert_eq!(choose_table_format(2, &[8, 40]), TableFormat::DefinitionList);
+/// assert_eq!(choose_table_format(4, &[8, 8, 8, 8]), TableFormat::Gfm);
+/// assert_eq!(choose_table_format(9, &[3; 9]), TableFormat::Ascii);
+/// ```
+pub fn choose_table_format(ncols: usize, col_widths: &[usize]) -> TableFormat {
+    if ncols == 2 && col_widths.first().is_some_and(|&width| width <= MAX_DEFINITION_TERM_WIDTH) {
+        TableFormat::DefinitionList
+    } else if ncols <= MAX_GFM_COLUMNS {
+        TableFormat::Gfm
+    } else {
+        TableFormat::Ascii
+    }
+}
+
+/// Maximum cell width (in characters) of each column
+/// ```
+/// use pdf_parser::tables::column_widths;
+///
+/// let rows = vec![vec!["Año".to_string(), "x".to_string()], vec!["2024".to_string()]];
+/// assert_eq!(column_widths(&rows), [4, 1]);
+/// ```
+pub fn column_widths(rows: &[Vec<String>]) -> Vec<usize> {
+    let mut widths = Vec::new();
+    for row in rows {
+        for (col, cell) in row.iter().enumerate() {
+            let width = cell.chars().count();
+            if col >= widths.len() {
+                widths.push(width);
+            } else if width > widths[col] {
+                widths[col] = width;
+            }
+        }
+    }
+    widths
+}
+
+/// Splits a Marker table's HTML into rows of plain-text cells. Both <th> and
+/// <td> cells are kept, in document order.
+/// ```
+/// use pdf_parser::tables::parse_table_rows;
+///
+/// let rows = parse_table_rows("<table><tr><th>Name</th><th>Year</th></tr><tr><td>A &amp; B</td><td>1</td></tr></table>");
+/// assert_eq!(rows, [["Name", "Year"], ["A & B", "1"]]);
+/// ```
+pub fn parse_table_rows(html: &str) -> Vec<Vec<String>> {
+    let row_re = Regex::new(r"(?is)<tr[^>]*>(.*?)</tr>").unwrap();
+    let cell_re = Regex::new(r"(?is)<t[hd][^>]*>(.*?)</t[hd]>").unwrap();
+
+    row_re
+        .captures_iter(html)
+        .map(|row| {
+            cell_re
+                .captures_iter(&row[1])
+                .map(|cell| extract_text_from_html(&cell[1]))
+                .collect::<Vec<_>>()
+        })
+        .filter(|cells| !cells.is_empty())
+        .collect()
+}
+
+/// Parses a Marker table's HTML into a row/column grid for export. Unlike
+/// parse_table_rows, a cell spanning several columns is repeated once per
+/// column so every column of the grid lines up with the header.
+/// ```
+/// use pdf_parser::tables::parse_html_table;
+///
+/// let rows = parse_html_table("<table><tr><th colspan=\"2\">Total</th></tr><tr><td>1</td><td>2</td></tr></table>")?;
+/// assert_eq!(rows, [["Total", "Total"], ["1", "2"]]);
+/// assert!(parse_html_table("<p>No table</p>").is_err());
+/// # Ok::<(), pdf_parser::tables::TableParseError>(())
+/// ```
+pub fn parse_html_table(html: &str) -> Result<Vec<Vec<String>>, TableParseError> {
+    let row_re = Regex::new(r"(?is)<tr[^>]*>(.*?)</tr>").unwrap();
+    let cell_re = Regex::new(r"(?is)<t[hd]([^>]*)>(.*?)</t[hd]>").unwrap();
+    let colspan_re = Regex::new(r#"(?i)\bcolspan\s*=\s*["']?([^"'\s>]*)"#).unwrap();
+
+    let mut rows = Vec::new();
+    for row in row_re.captures_iter(html) {
+        let mut cells = Vec::new();
+        for cell in cell_re.captures_iter(&row[1]) {
+            let span = match colspan_re.captures(&cell[1]) {
+                Some(caps) => match caps[1].parse::<usize>() {
+                    Ok(span) if (1..=MAX_COLSPAN).contains(&span) => span,
+                    _ => return Err(TableParseError::InvalidColspan(caps[1].to_string())),
+                },
+                None => 1,
+            };
+            let text = extract_text_from_html(&cell[2]);
+            cells.extend(std::iter::repeat_n(text, span));
+        }
+        if !cells.is_empty() {
+            rows.push(cells);
+        }
+    }
+
+    if rows.is_empty() {
+        return Err(TableParseError::NoRows);
+    }
+    Ok(rows)
+}
+
+/// Renders rows as plain text, one line per row with tab-separated cells
+/// ```
+/// use pdf_parser::tables::render_tab_separated;
+///
+/// let rows = vec![vec!["a".to_string(), "b".to_string()], vec!["c".to_string(), "d".to_string()]];
+/// assert_eq!(render_tab_separated(&rows), "a\tb\nc\td");
+/// ```
+pub fn render_tab_separated(rows: &[Vec<String>]) -> String {
+    rows.iter().map(|row| row.join("\t")).collect::<Vec<_>>().join("\n")
+}
+
+/// Renders rows as RFC 4180 CSV. Cells containing a comma, quote or line
+/// break are quoted, with embedded quotes doubled.
+/// ```
+/// use pdf_parser::tables::render_csv;
+///
+/// let rows = vec![vec!["name".to_string(), "quote".to_string()], vec!["Smith, J.".to_string(), "\"Hi\"".to_string()]];
+/// assert_eq!(render_csv(&rows), "name,quote\r\n\"Smith, J.\",\"\"\"Hi\"\"\"\r\n");
+/// ```
+pub fn render_csv(rows: &[Vec<String>]) -> String {
+    let mut csv = String::new();
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .map(|cell| {
+                if cell.contains([',', '"', '\n', '\r']) {
+                    format!("\"{}\"", cell.replace('"', "\"\""))
+                } else {
+                    cell.clone()
+                }
+            })
+            .collect();
+        csv.push_str(&cells.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// Renders rows as a GitHub-flavoured Markdown pipe table, treating the first
+/// row as the header. Short rows are padded so every row has the same width.
+/// ```
+/// use pdf_parser::tables::render_gfm_table;
+///
+/// let rows = vec![vec!["Name".to_string(), "Year".to_string()], vec!["A".to_string()]];
+/// assert_eq!(render_gfm_table(&rows), "| Name | Year |\n| --- | --- |\n| A |  |");
+/// ```
+pub fn render_gfm_table(rows: &[Vec<String>]) -> String {
+    let ncols = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    if ncols == 0 {
+        return String::new();
+    }
+
+    let mut lines = Vec::new();
+    for (index, row) in rows.iter().enumerate() {
+        let cells: Vec<String> = (0..ncols)
+            .map(|col| escape_pipes(row.get(col).map(String::as_str).unwrap_or("")))
+            .collect();
+        lines.push(format!("| {} |", cells.join(" | ")));
+        if index == 0 {
+            lines.push(format!("|{}", " --- |".repeat(ncols)));
+        }
+    }
+    lines.join("\n")
+}
+
+fn escape_pipes(cell: &str) -> String {
+    cell.replace('|', "\\|")
+}
+
+/// Renders each row as a `term: value` paragraph. The blank line between
+/// rows keeps Markdown from joining the pairs into one paragraph.
+/// ```
+/// use pdf_parser::tables::render_definition_list;
+///
+/// let rows = vec![vec!["Author".to_string(), "Smith".to_string()], vec!["Year".to_string(), "1990".to_string()]];
+/// assert_eq!(render_definition_list(&rows), "Author: Smith\n\nYear: 1990");
+/// ```
+pub fn render_definition_list(rows: &[Vec<String>]) -> String {
+    rows.iter()
+        .map(|row| {
+            let term = row.first().map(String::as_str).unwrap_or("");
+            let value = row.get(1).map(String::as_str).unwrap_or("");
+            format!("{}: {}", term, value)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Renders rows as a fixed-width ASCII grid, with `=` under the header row
+/// ```
+/// use pdf_parser::tables::render_ascii_table;
+///
+/// let rows = vec![vec!["Name".to_string(), "Year".to_string()], vec!["A".to_string(), "1990".to_string()]];
+/// assert_eq!(
+///     render_ascii_table(&rows),
+///     "+------+------+\n| Name | Year |\n+======+======+\n| A    | 1990 |\n+------+------+"
+/// );
+/// ```
+pub fn render_ascii_table(rows: &[Vec<String>]) -> String {
+    let widths = column_widths(rows);
+    if widths.is_empty() {
+        return String::new();
+    }
+
+    let border = |fill: char| {
+        let segments: Vec<String> = widths
+            .iter()
+            .map(|&width| fill.to_string().repeat(width + 2))
+            .collect();
+        format!("+{}+", segments.join("+"))
+    };
+
+    let mut lines = vec![border('-')];
+    for (index, row) in rows.iter().enumerate() {
+        let cells: Vec<String> = widths
+            .iter()
+            .enumerate()
+            .map(|(col, &width)| {
+                let cell = row.get(col).map(String::as_str).unwrap_or("");
+                let padding = width - cell.chars().count();
+                format!(" {}{} ", cell, " ".repeat(padding))
+            })
+            .collect();
+        lines.push(format!("|{}|", cells.join("|")));
+        lines.push(border(if index == 0 { '=' } else { '-' }));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An HTML table with a header row and one data row of `ncols` columns
+    fn table_html(ncols: usize) -> String {
+        let header: String = (1..=ncols).map(|col| format!("<th>Column {}</th>", col)).collect();
+        let row: String = (1..=ncols).map(|col| format!("<td>{}</td>", col)).collect();
+        format!("<table><tr>{}</tr><tr>{}</tr></table>", header, row)
+    }
+
+    fn format_of(html: &str) -> TableFormat {
+        let rows = parse_table_rows(html);
+        let widths = column_widths(&rows);
+        choose_table_format(widths.len(), &widths)
+    }
+
+    #[test]
+    fn chooses_format_by_column_count() {
+        assert_eq!(format_of(&table_html(1)), TableFormat::Gfm);
+        assert_eq!(format_of(&table_html(2)), TableFormat::DefinitionList);
+        assert_eq!(format_of(&table_html(5)), TableFormat::Gfm);
+        assert_eq!(format_of(&table_html(6)), TableFormat::Gfm);
+        assert_eq!(format_of(&table_html(7)), TableFormat::Ascii);
+        assert_eq!(format_of(&table_html(10)), TableFormat::Ascii);
+    }
+
+    fn grid(rows: &[&[&str]]) -> Vec<Vec<String>> {
+        rows.iter()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn parses_a_simple_two_by_three_table() {
+        let rows = parse_html_table(
+            "<table><tr><td>a</td><td>b</td><td>c</td></tr><tr><td>1</td><td>2</td><td>3</td></tr></table>",
+        )
+        .unwrap();
+        assert_eq!(rows, grid(&[&["a", "b", "c"], &["1", "2", "3"]]));
+    }
+
+    #[test]
+    fn header_cells_are_a_row_like_any_other() {
+        let html = "<table>\n<thead><tr><th>Name</th><th scope=\"col\">Year</th></tr></thead>\n\
+                    <tbody><tr><td>Ada</td><td>1815</td></tr></tbody></table>";
+        assert_eq!(parse_html_table(html).unwrap(), grid(&[&["Name", "Year"], &["Ada", "1815"]]));
+    }
+
+    #[test]
+    fn colspan_repeats_the_cell_in_each_column() {
+        let html = "<table><tr><th colspan=\"2\">Revenue</th><th>Year</th></tr>\
+                    <tr><td>10</td><td>12</td><td colspan='1'>2024</td></tr></table>";
+        assert_eq!(
+            parse_html_table(html).unwrap(),
+            grid(&[&["Revenue", "Revenue", "Year"], &["10", "12", "2024"]])
+        );
+    }
+
+    #[test]
+    fn cell_entities_and_markup_are_decoded() {
+        let html = "<table><tr><td>Fish &amp; <b>chips</b></td><td>&lt;5&gt;</td><td>&quot;x&quot; &#8212; 10&nbsp;%</td></tr></table>";
+        // Whitespace is collapsed after decoding, no-break spaces included
+        assert_eq!(parse_html_table(html).unwrap(), grid(&[&["Fish & chips", "<5>", "\"x\" — 10 %"]]));
+    }
+
+    #[test]
+    fn malformed_tables_are_errors() {
+        assert!(matches!(parse_html_table("<p>No rows</p>"), Err(TableParseError::NoRows)));
+        assert!(matches!(parse_html_table("<table><tr></tr></table>"), Err(TableParseError::NoRows)));
+        for colspan in ["0", "x", "100000"] {
+            let html = format!("<table><tr><td colspan=\"{}\">a</td></tr></table>", colspan);
+            assert!(
+                matches!(parse_html_table(&html), Err(TableParseError::InvalidColspan(ref value)) if value == colspan),
+                "colspan {}",
+                colspan
+            );
+        }
+    }
+
+    #[test]
+    fn two_columns_with_a_long_first_column_stay_a_grid() {
+        let long_term = "x".repeat(MAX_DEFINITION_TERM_WIDTH + 1);
+        let html = format!("<table><tr><td>{}</td><td>value</td></tr></table>", long_term);
+        assert_eq!(format_of(&html), TableFormat::Gfm);
+    }
+
+    #[test]
+    fn definition_list_pairs_are_separate_paragraphs() {
+        let rows = parse_table_rows(
+            "<table><tr><td>Name</td><td>Ada</td></tr><tr><td>Born</td><td>1815</td></tr></table>",
+        );
+        assert_eq!(render_definition_list(&rows), "Name: Ada\n\nBorn: 1815");
+    }
+
+    #[test]
+    fn gfm_table_pads_short_rows_and_escapes_pipes() {
+        let rows = vec![
+            vec!["a".to_string(), "b|c".to_string()],
+            vec!["1".to_string()],
+        ];
+        assert_eq!(render_gfm_table(&rows), "| a | b\\|c |\n| --- | --- |\n| 1 |  |");
+    }
+
+    #[test]
+    fn ascii_table_aligns_columns() {
+        let rows = vec![
+            vec!["id".to_string(), "name".to_string()],
+            vec!["10".to_string(), "Ada".to_string()],
+        ];
+        assert_eq!(
+            render_ascii_table(&rows),
+            "+----+------+\n| id | name |\n+====+======+\n| 10 | Ada  |\n+----+------+"
+        );
+    }
+}
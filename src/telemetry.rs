@@ -0,0 +1,175 @@
+// OpenTelemetry tracing of file processing, compiled in with the `otel`
+// feature. Each processed file gets a `process_file` span with its
+// input_file, block_count, output_format, duration_ms and, on failure, error.
+// Without the feature trace_file only runs the closure and shutdown does
+// nothing, so the default build carries no tracing code.
+use pdf_parser::OutputFormat;
+use std::error::Error;
+use std::path::Path;
+
+#[cfg(feature = "otel")]
+use opentelemetry::KeyValue;
+#[cfg(feature = "otel")]
+use opentelemetry::trace::{Span, Status, Tracer};
+#[cfg(feature = "otel")]
+use opentelemetry_sdk::trace::SdkTracerProvider;
+#[cfg(feature = "otel")]
+use std::sync::Mutex;
+#[cfg(feature = "otel")]
+use std::time::Instant;
+
+#[cfg(feature = "otel")]
+pub const DEFAULT_ENDPOINT: &str = "http://localhost:4317";
+
+#[cfg(feature = "otel")]
+struct Telemetry {
+    provider: SdkTracerProvider,
+    // The gRPC exporter runs its connection on this runtime, which must
+    // outlive the provider
+    _runtime: tokio::runtime::Runtime,
+}
+
+#[cfg(feature = "otel")]
+static TELEMETRY: Mutex<Option<Telemetry>> = Mutex::new(None);
+
+// Sets up a batching OTLP/gRPC exporter to `endpoint` as the global tracer
+// provider
+#[cfg(feature = "otel")]
+pub fn init(endpoint: &str) -> Result<(), Box<dyn Error>> {
+    use opentelemetry_otlp::WithExportConfig;
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()?;
+    let exporter = runtime.block_on(async {
+        opentelemetry_otlp::SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .build()
+    })?;
+    let provider = SdkTracerProvider::builder().with_batch_exporter(exporter).build();
+    opentelemetry::global::set_tracer_provider(provider.clone());
+
+    *TELEMETRY.lock().unwrap() = Some(Telemetry {
+        provider,
+        _runtime: runtime,
+    });
+    Ok(())
+}
+
+// Flushes the spans still queued for export. Called before the process
+// exits, since statics are never dropped. The provider is taken out of its
+// slot, so calls after the first do nothing.
+pub fn shutdown() {
+    #[cfg(feature = "otel")]
+    if let Some(telemetry) = TELEMETRY.lock().unwrap().take()
+        && let Err(e) = telemetry.provider.shutdown()
+    {
+        eprintln!("Could not export traces: {}", e);
+    }
+}
+
+// Runs `process` for `input_path` inside a trace span, taking the block
+// count of a successful result from `block_count`
+#[cfg(feature = "otel")]
+pub fn trace_file<T>(
+    input_path: &Path,
+    output_formats: &[OutputFormat],
+    process: impl FnOnce() -> Result<T, Box<dyn Error>>,
+    block_count: impl FnOnce(&T) -> usize,
+) -> Result<T, Box<dyn Error>> {
+    let tracer = opentelemetry::global::tracer("flatten_marker_output");
+    let mut span = tracer.start("process_file");
+    span.set_attribute(KeyValue::new("input_file", input_path.display().to_string()));
+    span.set_attribute(KeyValue::new("output_format", format_names(output_formats)));
+
+    let started = Instant::now();
+    let result = process();
+    span.set_attribute(KeyValue::new("duration_ms", started.elapsed().as_millis() as i64));
+    match &result {
+        Ok(value) => span.set_attribute(KeyValue::new("block_count", block_count(value) as i64)),
+        Err(e) => {
+            span.set_attribute(KeyValue::new("error", e.to_string()));
+            span.set_status(Status::error(e.to_string()));
+        }
+    }
+    span.end();
+    result
+}
+
+#[cfg(not(feature = "otel"))]
+#[inline(always)]
+pub fn trace_file<T>(
+    _input_path: &Path,
+    _output_formats: &[OutputFormat],
+    process: impl FnOnce() -> Result<T, Box<dyn Error>>,
+    _block_count: impl FnOnce(&T) -> usize,
+) -> Result<T, Box<dyn Error>> {
+    process()
+}
+
+// "json,markdown" for --multi-format json,markdown
+#[cfg(feature = "otel")]
+fn format_names(output_formats: &[OutputFormat]) -> String {
+    use clap::ValueEnum;
+
+    output_formats
+        .iter()
+        .filter_map(|format| format.to_possible_value())
+        .map(|value| value.get_name().to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(all(test, feature = "otel"))]
+mod tests {
+    use super::*;
+    use opentelemetry::Value;
+    use opentelemetry_sdk::trace::InMemorySpanExporter;
+
+    fn attribute(span: &opentelemetry_sdk::trace::SpanData, key: &str) -> Option<Value> {
+        span.attributes
+            .iter()
+            .find(|attribute| attribute.key.as_str() == key)
+            .map(|attribute| attribute.value.clone())
+    }
+
+    #[test]
+    fn each_file_is_exported_as_a_span_with_its_attributes() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        opentelemetry::global::set_tracer_provider(provider.clone());
+
+        let formats = [OutputFormat::Json, OutputFormat::Markdown];
+        let blocks = trace_file(Path::new("in/a.json"), &formats, || Ok(vec![1, 2, 3]), Vec::len).unwrap();
+        assert_eq!(blocks.len(), 3);
+        let failed = trace_file(
+            Path::new("in/b.json"),
+            &formats[..1],
+            || Err::<Vec<u8>, _>("Invalid JSON schema".into()),
+            Vec::len,
+        );
+        assert!(failed.is_err());
+        provider.force_flush().unwrap();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 2);
+        assert!(spans.iter().all(|span| span.name == "process_file"));
+
+        let ok = &spans[0];
+        assert_eq!(attribute(ok, "input_file"), Some(Value::from("in/a.json")));
+        assert_eq!(attribute(ok, "output_format"), Some(Value::from("json,markdown")));
+        assert_eq!(attribute(ok, "block_count"), Some(Value::I64(3)));
+        assert!(matches!(attribute(ok, "duration_ms"), Some(Value::I64(ms)) if ms >= 0));
+        assert_eq!(attribute(ok, "error"), None);
+
+        let err = &spans[1];
+        assert_eq!(attribute(err, "input_file"), Some(Value::from("in/b.json")));
+        assert_eq!(attribute(err, "error"), Some(Value::from("Invalid JSON schema")));
+        assert_eq!(attribute(err, "block_count"), None);
+        assert!(matches!(err.status, Status::Error { .. }));
+    }
+}
@@ -0,0 +1,65 @@
+// Shared tokenizer for the corpus analysis subcommands. The corpus this tool
+// was written for is mostly Spanish, so the stopword list covers both Spanish
+// and English function words.
+const STOPWORDS: &[&str] = &[
+    // English
+    "a", "about", "after", "all", "also", "an", "and", "any", "are", "as", "at", "be", "been",
+    "but", "by", "can", "do", "for", "from", "has", "have", "he", "her", "his", "how", "i", "if",
+    "in", "into", "is", "it", "its", "more", "my", "no", "not", "of", "on", "one", "or", "our",
+    "she", "so", "some", "such", "than", "that", "the", "their", "them", "then", "there", "these",
+    "they", "this", "those", "to", "up", "was", "we", "were", "what", "when", "which", "who",
+    "will", "with", "you", "your",
+    // Spanish
+    "al", "algo", "ante", "con", "cual", "como", "de", "del", "desde", "donde", "el", "ella",
+    "ellas", "ellos", "en", "entre", "era", "es", "esa", "ese", "eso", "esta", "este", "esto",
+    "fue", "ha", "han", "hay", "la", "las", "le", "les", "lo", "los", "mas", "me", "mi", "muy",
+    "más", "ni", "nos", "o", "para", "pero", "por", "que", "qué", "se", "sea", "ser", "si", "sin",
+    "sobre", "son", "su", "sus", "sí", "también", "te", "tu", "tus", "un", "una", "uno", "unos",
+    "unas", "y", "ya", "él",
+];
+
+/// Lowercases `text`, splits it on anything that isn't a letter or digit and
+/// drops stopwords, single characters and purely numeric tokens
+/// ```
+/// use pdf_parser::tokenize::tokenize;
+///
+/// assert_eq!(tokenize("La Historia de the World, 1990!"), ["historia", "world"]);
+/// ```
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_lowercase())
+        .filter(|token| token.chars().count() > 1)
+        .filter(|token| !token.chars().all(|c| c.is_numeric()))
+        .filter(|token| !is_stopword(token))
+        .collect()
+}
+
+/// Whether `token` (lowercase) is a Spanish or English function word
+/// ```
+/// use pdf_parser::tokenize::is_stopword;
+///
+/// assert!(is_stopword("the") && is_stopword("de"));
+/// assert!(!is_stopword("historia"));
+/// ```
+pub fn is_stopword(token: &str) -> bool {
+    STOPWORDS.contains(&token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_stopwords_numbers_and_single_characters() {
+        assert_eq!(
+            tokenize("2. The Results of a Study, y los Resultados del 2024 (A)"),
+            vec!["results", "study", "resultados"]
+        );
+    }
+
+    #[test]
+    fn keeps_accented_words_whole() {
+        assert_eq!(tokenize("Introducción: Análisis"), vec!["introducción", "análisis"]);
+    }
+}
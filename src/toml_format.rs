@@ -0,0 +1,121 @@
+// TOML serialization of a block list, as a `[[blocks]]` array of tables.
+// Reading it back gives the blocks exactly as written, so a processed
+// document can be edited by hand and fed through the tool again.
+use crate::Block;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+struct TomlDocument {
+    blocks: Vec<Block>,
+}
+
+/// Serializes blocks as a `[[blocks]]` array of tables
+/// ```
+/// use pdf_parser::Block;
+/// use pdf_parser::toml_format::{blocks_from_toml, blocks_to_toml};
+///
+/// let block = Block {
+///     id: "/page/0/Text/0".to_string(),
+///     block_type: "Text".to_string(),
+///     text: "Line one\nLine two".to_string(),
+///     ..Default::default()
+/// };
+/// let toml = blocks_to_toml(std::slice::from_ref(&block))?;
+/// assert!(toml.starts_with("[[blocks]]\n"));
+/// assert_eq!(blocks_from_toml(&toml).unwrap()[0].text, block.text);
+/// # Ok::<(), toml::ser::Error>(())
+/// ```
+pub fn blocks_to_toml(blocks: &[Block]) -> Result<String, toml::ser::Error> {
+    #[derive(Serialize)]
+    struct TomlDocumentRef<'a> {
+        blocks: &'a [Block],
+    }
+
+    toml::to_string_pretty(&TomlDocumentRef { blocks })
+}
+
+/// Reads blocks written by blocks_to_toml, or edited by hand
+/// ```
+/// use pdf_parser::toml_format::blocks_from_toml;
+///
+/// let blocks = blocks_from_toml("[[blocks]]\nid = \"/page/0/Text/0\"\nblock_type = \"Text\"\ntext = \"Edited\"\n")?;
+/// assert_eq!(blocks[0].text, "Edited");
+/// assert!(blocks_from_toml("[[blocks]]\nid = 1\n").is_err());
+/// # Ok::<(), toml::de::Error>(())
+/// ```
+pub fn blocks_from_toml(content: &str) -> Result<Vec<Block>, toml::de::Error> {
+    toml::from_str::<TomlDocument>(content).map(|document| document.blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(id: &str, block_type: &str, html: &str, text: &str) -> Block {
+        Block {
+            id: id.to_string(),
+            block_type: block_type.to_string(),
+            html: html.to_string(),
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn round_trip(blocks: &[Block]) -> Vec<Block> {
+        blocks_from_toml(&blocks_to_toml(blocks).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn blocks_round_trip() {
+        let mut table = block(
+            "/page/1/Table/3",
+            "Table",
+            "<table><tr><td>a</td></tr></table>",
+            "a",
+        );
+        table.bbox = Some(vec![10.0, 20.5, 300.0, 400.25]);
+        table.polygon = Some(vec![vec![10.0, 20.5], vec![300.0, 20.5]]);
+        table.section_hierarchy = Some(serde_json::json!({ "1": "/page/0/SectionHeader/0" }));
+        table.table_csv_path = Some("doc_table_0.csv".to_string());
+        let blocks = vec![
+            block("/page/0/SectionHeader/0", "SectionHeader", "<h1>Intro</h1>", "Intro"),
+            block(
+                "/page/0/Text/1",
+                "Text",
+                "<p>Quotes \" and \\ backslashes</p>",
+                "Line one\nLine two with \"quotes\", a tab\tand ünïcödé 日本",
+            ),
+            table,
+        ];
+
+        let read_back = round_trip(&blocks);
+        assert_eq!(
+            serde_json::to_value(&read_back).unwrap(),
+            serde_json::to_value(&blocks).unwrap()
+        );
+    }
+
+    #[test]
+    fn output_is_an_array_of_blocks_tables() {
+        let toml = blocks_to_toml(&[block("a", "Text", "<p>x</p>", "x")]).unwrap();
+        assert!(toml.starts_with("[[blocks]]\n"), "{}", toml);
+        assert!(toml.contains("block_type = \"Text\""), "{}", toml);
+        assert!(!toml.contains("bbox"), "{}", toml);
+    }
+
+    #[test]
+    fn empty_list_and_missing_fields() {
+        assert!(round_trip(&[]).is_empty());
+
+        let blocks = blocks_from_toml("[[blocks]]\ntext = \"edited by hand\"\n").unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].text, "edited by hand");
+        assert_eq!(blocks[0].id, "");
+    }
+
+    #[test]
+    fn invalid_toml_is_an_error() {
+        assert!(blocks_from_toml("[[blocks]\ntext = 1").is_err());
+        assert!(blocks_from_toml("[[blocks]]\ntext = 1\n").is_err());
+    }
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use common::{marker_json, path_str, read, run_ok, write};
+
+fn fixture(dir: &std::path::Path) -> std::path::PathBuf {
+    let input = dir.join("doc.json");
+    write(
+        &input,
+        &marker_json(&[
+            ("SectionHeader", "<h1>Título</h1>"),
+            ("Text", "<p>Première partie &amp; 日本</p>"),
+        ]),
+    );
+    input
+}
+
+#[test]
+fn encoded_output_decodes_back_to_the_plain_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = fixture(dir.path());
+
+    let plain = dir.path().join("plain");
+    run_ok(&[path_str(&input), "-o", path_str(&plain)]);
+    let encoded = dir.path().join("encoded");
+    run_ok(&[path_str(&input), "-o", path_str(&encoded), "--base64-encode-text"]);
+    assert_ne!(read(&encoded.join("doc_processed.json")), read(&plain.join("doc_processed.json")));
+
+    let decoded = dir.path().join("decoded");
+    run_ok(&[
+        path_str(&encoded.join("doc_processed.json")),
+        "-o",
+        path_str(&decoded),
+        "--base64-decode",
+    ]);
+    assert_eq!(
+        read(&decoded.join("doc_processed_processed.json")),
+        read(&plain.join("doc_processed.json"))
+    );
+}
+
+// Two pages of different sizes, which --detect-doc-boundaries splits into
+// two documents
+fn two_documents(dir: &std::path::Path) -> std::path::PathBuf {
+    let page = |index: usize, bbox: [f64; 4], text: &str| {
+        serde_json::json!({
+            "id": format!("/page/{}/Page/0", index),
+            "block_type": "Page",
+            "html": "",
+            "bbox": bbox,
+            "children": [{
+                "id": format!("/page/{}/Text/1", index),
+                "block_type": "Text",
+                "html": format!("<p>{}</p>", text),
+            }],
+        })
+    };
+    let input = dir.join("combined.json");
+    let document = serde_json::json!({
+        "children": [
+            page(0, [0.0, 0.0, 612.0, 792.0], "Letter-sized report"),
+            page(1, [0.0, 0.0, 595.0, 842.0], "A4 appendix"),
+        ]
+    });
+    write(&input, &document.to_string());
+    input
+}
+
+#[test]
+fn documents_are_split_before_their_block_type_is_encoded() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = two_documents(dir.path());
+    let out = dir.path().join("out");
+    run_ok(&[
+        path_str(&input),
+        "-o",
+        path_str(&out),
+        "--detect-doc-boundaries",
+        "--split-at-boundaries",
+        "--base64-encode-text",
+        "--base64-fields",
+        "block-type,text",
+    ]);
+
+    for (number, text) in [(1, "Letter-sized report"), (2, "A4 appendix")] {
+        let path = out.join(format!("combined_doc{}_processed.json", number));
+        let blocks: serde_json::Value = serde_json::from_str(&read(&path)).unwrap();
+        assert_eq!(blocks.as_array().unwrap().len(), 1, "{}", blocks);
+        assert_eq!(blocks[0]["block_type"], "VGV4dA==");
+        let decoded = STANDARD.decode(blocks[0]["text"].as_str().unwrap()).unwrap();
+        assert_eq!(String::from_utf8(decoded).unwrap(), text);
+    }
+    assert!(!out.join("combined_doc3_processed.json").exists());
+}
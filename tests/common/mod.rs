@@ -0,0 +1,66 @@
+// Helpers shared by the command-line integration tests
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+// Runs the flatten_marker_output binary with `args`
+pub fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_flatten_marker_output"))
+        .args(args)
+        .output()
+        .expect("failed to run flatten_marker_output")
+}
+
+// Runs the binary and panics with its output if it did not exit successfully
+pub fn run_ok(args: &[&str]) -> Output {
+    let output = run(args);
+    assert!(
+        output.status.success(),
+        "flatten_marker_output {:?} failed:\nstdout: {}\nstderr: {}",
+        args,
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+pub fn write(path: &Path, contents: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).unwrap();
+    }
+    fs::write(path, contents).unwrap();
+}
+
+pub fn read(path: &Path) -> String {
+    fs::read_to_string(path).unwrap_or_else(|e| panic!("could not read {:?}: {}", path, e))
+}
+
+pub fn path_str(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
+// A one-page Marker document with the given (block_type, html) children
+pub fn marker_json(blocks: &[(&str, &str)]) -> String {
+    let children: Vec<serde_json::Value> = blocks
+        .iter()
+        .enumerate()
+        .map(|(index, (block_type, html))| {
+            serde_json::json!({
+                "id": format!("/page/0/{}/{}", block_type, index),
+                "block_type": block_type,
+                "html": html,
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "children": [{
+            "id": "/page/0/Page/0",
+            "block_type": "Page",
+            "html": "",
+            "children": children,
+        }]
+    })
+    .to_string()
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use common::{marker_json, path_str, read, run_ok, write};
+use std::path::Path;
+
+fn fixture() -> String {
+    marker_json(&[
+        ("SectionHeader", "<h1>Annual Report</h1>"),
+        ("Text", "<p>Revenue grew.</p>"),
+        ("Table", "<table><tr><th>Year</th><th>Revenue</th></tr><tr><td>2024</td><td>10</td></tr></table>"),
+    ])
+}
+
+fn stderr_of(args: &[&str]) -> String {
+    String::from_utf8_lossy(&run_ok(args).stderr).into_owned()
+}
+
+#[test]
+fn duplicates_get_every_output_under_their_own_name() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("in");
+    let output = dir.path().join("out");
+    write(&input.join("a.json"), &fixture());
+    write(&input.join("sub/b.json"), &fixture());
+    write(&input.join("c.json"), &marker_json(&[("Text", "<p>Something else</p>")]));
+
+    let stderr = stderr_of(&[
+        path_str(&input),
+        "-o",
+        path_str(&output),
+        "--multi-format",
+        "json,markdown",
+        "--yaml-front-matter",
+        "--stats",
+        "--content-hash",
+        "--extract-tables",
+    ]);
+    assert_eq!(stderr.matches("Skipped duplicate of:").count(), 1, "{}", stderr);
+    assert!(stderr.contains(&format!("Skipped duplicate of: {}", input.canonicalize().unwrap().join("a.json").display())));
+
+    let sub = output.join("sub");
+    for name in ["b_processed.json", "b_processed.md", "b_stats.json", "b.sha256", "b_table_0.csv"] {
+        assert!(sub.join(name).is_file(), "missing {}", name);
+    }
+    assert!(!sub.join("a_table_0.csv").exists());
+
+    let blocks: serde_json::Value = serde_json::from_str(&read(&sub.join("b_processed.json"))).unwrap();
+    assert_eq!(blocks[2]["table_csv_path"], "b_table_0.csv");
+
+    let markdown = read(&sub.join("b_processed.md"));
+    assert!(
+        markdown.lines().any(|line| line.starts_with("source_file: ") && line.ends_with("b.json")),
+        "{}",
+        markdown
+    );
+    assert!(!markdown.contains("a.json"), "{}", markdown);
+
+    let stats: serde_json::Value = serde_json::from_str(&read(&sub.join("b_stats.json"))).unwrap();
+    assert!(stats["input_file"].as_str().unwrap().ends_with("b.json"));
+    assert_eq!(read(&sub.join("b.sha256")), read(&output.join("a.sha256")));
+
+    let summary: serde_json::Value = serde_json::from_str(&read(&output.join("_summary_stats.json"))).unwrap();
+    assert_eq!(summary["files_processed"], 2);
+    assert_eq!(summary["files_skipped_duplicate"], 1);
+}
+
+#[test]
+fn no_dedup_processes_every_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("in");
+    let output = dir.path().join("out");
+    write(&input.join("a.json"), &fixture());
+    write(&input.join("b.json"), &fixture());
+
+    let stderr = stderr_of(&[path_str(&input), "-o", path_str(&output), "--no-dedup"]);
+    assert!(!stderr.contains("Skipped duplicate"), "{}", stderr);
+    assert_eq!(stderr.matches("Processing JSON file").count(), 2);
+    assert_eq!(read(&output.join("a_processed.json")), read(&output.join("b_processed.json")));
+}
+
+#[test]
+fn files_differing_only_in_content_are_not_duplicates() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("in");
+    write(&input.join("a.json"), &fixture());
+    write(&input.join("b.json"), &fixture().replace("grew", "fell"));
+
+    let stderr = stderr_of(&[path_str(&input), "-o", path_str(&dir.path().join("out"))]);
+    assert!(!stderr.contains("Skipped duplicate"), "{}", stderr);
+    assert!(Path::new(&dir.path().join("out/b_processed.json")).is_file());
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use common::{marker_json, path_str, read, run, run_ok};
+use std::fs;
+
+// A UTF-8 Marker file with one Windows-1252 curly quote pair spliced in
+fn mixed_encoding_input(dir: &std::path::Path) -> std::path::PathBuf {
+    let json = marker_json(&[("Text", "<p>Café QUOTE naïve 日本</p>")]);
+    let bytes = json.replacen("QUOTE", "\u{1}", 1).into_bytes();
+    let bytes: Vec<u8> = bytes
+        .into_iter()
+        .flat_map(|byte| if byte == 1 { vec![0x93, b'x', 0x94] } else { vec![byte] })
+        .collect();
+    let input = dir.join("mixed.json");
+    fs::write(&input, bytes).unwrap();
+    input
+}
+
+#[test]
+fn only_invalid_bytes_of_mixed_input_are_redecoded() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = mixed_encoding_input(dir.path());
+    let out = dir.path().join("out");
+
+    run_ok(&[path_str(&input), "-o", path_str(&out), "--output-format", "text"]);
+    assert_eq!(read(&out.join("mixed_processed.txt")).trim(), "Café “x” naïve 日本");
+}
+
+#[test]
+fn strict_utf8_rejects_mixed_input() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = mixed_encoding_input(dir.path());
+    let out = dir.path().join("out");
+
+    let output = run(&[path_str(&input), "-o", path_str(&out), "--strict-utf8"]);
+    assert!(!output.status.success());
+    assert!(!out.join("mixed_processed.json").exists());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("is not valid UTF-8"));
+}
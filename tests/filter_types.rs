@@ -0,0 +1,100 @@
+mod common;
+
+use common::{path_str, read, run_ok, write};
+use std::path::{Path, PathBuf};
+
+// One page with a header, heading, text, a caption, a large and a small
+// picture (by bounding box) and a footer
+fn fixture(dir: &Path) -> PathBuf {
+    let block = |index: usize, block_type: &str, html: &str, bbox: [f64; 4]| {
+        serde_json::json!({
+            "id": format!("/page/0/{}/{}", block_type, index),
+            "block_type": block_type,
+            "html": html,
+            "bbox": bbox,
+        })
+    };
+    let document = serde_json::json!({
+        "children": [{
+            "id": "/page/0/Page/0",
+            "block_type": "Page",
+            "html": "",
+            "children": [
+                block(0, "PageHeader", "<p>Running head</p>", [0.0, 0.0, 600.0, 20.0]),
+                block(1, "SectionHeader", "<h1>Methods</h1>", [0.0, 40.0, 600.0, 60.0]),
+                block(2, "Text", "<p>We measured things.</p>", [0.0, 70.0, 600.0, 120.0]),
+                block(3, "Picture", "<img alt=\"Setup diagram\">", [0.0, 130.0, 400.0, 430.0]),
+                block(4, "Picture", "<img alt=\"Bullet icon\">", [0.0, 440.0, 10.0, 450.0]),
+                block(5, "Caption", "<p>Figure 1. Setup.</p>", [0.0, 460.0, 600.0, 480.0]),
+                block(6, "PageFooter", "<p>Page 1</p>", [0.0, 770.0, 600.0, 790.0]),
+            ],
+        }]
+    });
+    let input = dir.join("doc.json");
+    write(&input, &document.to_string());
+    input
+}
+
+// Block types of the processed output, in order
+fn output_types(input: &Path, args: &[&str]) -> Vec<String> {
+    let mut all_args = vec![path_str(input)];
+    all_args.extend(args);
+    run_ok(&all_args);
+    let output = input.with_file_name("doc_processed.json");
+    let blocks: Vec<serde_json::Value> = serde_json::from_str(&read(&output)).unwrap();
+    blocks
+        .iter()
+        .map(|block| block["block_type"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn default_run_drops_headers_footers_and_pictures() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = fixture(dir.path());
+    assert_eq!(output_types(&input, &[]), ["SectionHeader", "Text", "Caption"]);
+}
+
+#[test]
+fn include_only_types_drops_everything_else() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = fixture(dir.path());
+    assert_eq!(
+        output_types(&input, &["--include-only-types", "Text,SectionHeader"]),
+        ["SectionHeader", "Text"]
+    );
+}
+
+#[test]
+fn exclude_types_replaces_the_default_list() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = fixture(dir.path());
+    // The page header and footer the default run discards are kept
+    assert_eq!(
+        output_types(&input, &["--exclude-types", "Picture"]),
+        ["PageHeader", "SectionHeader", "Text", "Caption", "PageFooter"]
+    );
+    // Pictures are kept, but the small one falls under the default minimum area
+    assert_eq!(
+        output_types(&input, &["--exclude-types", "PageHeader,PageFooter,Caption"]),
+        ["SectionHeader", "Text", "Picture"]
+    );
+}
+
+#[test]
+fn min_picture_area_applies_without_keep_pictures() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = fixture(dir.path());
+    assert_eq!(
+        output_types(&input, &["--exclude-types", "PageHeader,PageFooter", "--min-picture-area", "0"]),
+        ["SectionHeader", "Text", "Picture", "Picture", "Caption"]
+    );
+    assert_eq!(
+        output_types(&input, &["--include-only-types", "Picture", "--min-picture-area", "50"]),
+        ["Picture", "Picture"]
+    );
+    assert_eq!(
+        output_types(&input, &["--include-only-types", "Picture", "--min-picture-area", "500"]),
+        ["Picture"]
+    );
+}
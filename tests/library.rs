@@ -0,0 +1,80 @@
+// Uses the processing pipeline as a dependency would
+use pdf_parser::{Block, Document, ProcessingConfig, process_document};
+
+fn block(id: &str, block_type: &str, html: &str) -> Block {
+    Block {
+        id: id.to_string(),
+        block_type: block_type.to_string(),
+        html: html.to_string(),
+        ..Default::default()
+    }
+}
+
+fn page(children: Vec<Block>) -> Block {
+    Block {
+        id: "/page/0/Page/0".to_string(),
+        block_type: "Page".to_string(),
+        children: Some(children),
+        ..Default::default()
+    }
+}
+
+fn document() -> Document {
+    Document {
+        children: vec![page(vec![
+            block("/page/0/PageHeader/0", "PageHeader", "<p>Running head</p>"),
+            block("/page/0/SectionHeader/1", "SectionHeader", "<h1>Methods</h1>"),
+            block("/page/0/Text/2", "Text", "<p>We used machine learning (ML) &amp; statistics.</p>"),
+            block("/page/0/Text/3", "Text", "<p>ML helped.</p>"),
+            block("/page/0/Text/4", "Text", "<p>#### ~~~~ #### ~~~~ ####</p>"),
+        ])],
+    }
+}
+
+#[test]
+fn default_config_flattens_and_filters() {
+    let processed = process_document(document(), &ProcessingConfig::default());
+
+    let ids: Vec<&str> = processed.blocks.iter().map(|block| block.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        ["/page/0/SectionHeader/1", "/page/0/Text/2", "/page/0/Text/3", "/page/0/Text/4"]
+    );
+    assert_eq!(processed.blocks[1].text, "We used machine learning (ML) & statistics.");
+    assert_eq!(processed.input_block_count, 5);
+    assert_eq!(processed.output_block_count, 4);
+    assert_eq!(processed.block_type_histogram["Text"], 3);
+    assert_eq!(processed.block_type_histogram["SectionHeader"], 1);
+
+    // Passes that are off report nothing
+    assert!(processed.abbreviations.is_empty());
+    assert!(processed.punctuation_flags.is_empty());
+    assert_eq!(processed.document_boundaries, 0);
+}
+
+#[test]
+fn pass_results_are_returned_instead_of_printed() {
+    let config = ProcessingConfig {
+        expand_abbreviations: true,
+        drop_punctuation_noise: true,
+        ..Default::default()
+    };
+    let processed = process_document(document(), &config);
+
+    assert_eq!(processed.abbreviations["ML"], "machine learning");
+    assert_eq!(processed.abbreviations_expanded, 1);
+    assert_eq!(processed.blocks[2].text, "machine learning (ML) helped.");
+
+    let flagged: Vec<&str> = processed.punctuation_flags.iter().map(|flag| flag.id.as_str()).collect();
+    assert_eq!(flagged, ["/page/0/Text/4"]);
+    assert_eq!(processed.output_block_count, 3);
+    assert!(processed.blocks.iter().all(|block| block.id != "/page/0/Text/4"));
+}
+
+#[test]
+fn processed_documents_serialize() {
+    let processed = process_document(document(), &ProcessingConfig::default());
+    let json = serde_json::to_value(&processed).unwrap();
+    assert_eq!(json["output_block_count"], 4);
+    assert_eq!(json["blocks"][0]["text"], "Methods");
+}
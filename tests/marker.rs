@@ -0,0 +1,235 @@
+// Runs PDF input through shell scripts standing in for Marker
+#![cfg(unix)]
+
+mod common;
+
+use common::{marker_json, path_str, read, run, run_ok, write};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+const EXPECTED: &str = r#"[
+  {
+    "id": "/page/0/Text/0",
+    "block_type": "Text",
+    "html": "<p>Converted text.</p>",
+    "text": "Converted text."
+  }
+]"#;
+
+fn fixture() -> String {
+    marker_json(&[("Text", "<p>Converted text.</p>")])
+}
+
+// Writes an executable script into `dir`. Like Marker, each mock writes
+// <output_dir>/<stem>/<stem>.json plus a meta file and an image, and logs
+// its arguments to calls.log.
+fn mock(dir: &Path, name: &str, body: &str) -> PathBuf {
+    let fixture_path = dir.join("fixture.json");
+    write(&fixture_path, &fixture());
+    let script = dir.join(name);
+    write(
+        &script,
+        &format!(
+            "#!/bin/sh\n\
+             echo \"$@\" >> '{log}'\n\
+             [ \"$2\" = --output_format ] && [ \"$3\" = json ] && [ \"$4\" = --output_dir ] || exit 64\n\
+             convert() {{\n\
+                 stem=$(basename \"$1\" .pdf)\n\
+                 mkdir -p \"$2/$stem\"\n\
+                 cp '{fixture}' \"$2/$stem/$stem.json\"\n\
+                 echo '{{}}' > \"$2/$stem/${{stem}}_meta.json\"\n\
+                 echo image > \"$2/$stem/_page_0_Picture_1.jpeg\"\n\
+             }}\n\
+             {body}\n",
+            log = dir.join("calls.log").display(),
+            fixture = fixture_path.display(),
+            body = body
+        ),
+    );
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    script
+}
+
+fn marker_single(dir: &Path) -> PathBuf {
+    mock(dir, "marker_single", "convert \"$1\" \"$5\"")
+}
+
+fn marker_batch(dir: &Path) -> PathBuf {
+    mock(dir, "marker", "for pdf in \"$1\"/*.pdf; do convert \"$pdf\" \"$5\"; done")
+}
+
+// Every file below `dir`, relative to it
+fn files(dir: &Path) -> Vec<String> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        if path.is_dir() {
+            files.extend(files_in(&path, &name));
+        } else {
+            files.push(name);
+        }
+    }
+    files.sort();
+    files
+}
+
+fn files_in(dir: &Path, prefix: &str) -> Vec<String> {
+    files(dir).into_iter().map(|name| format!("{}/{}", prefix, name)).collect()
+}
+
+#[test]
+fn pdf_is_converted_and_marker_files_are_not_left_behind() {
+    let mocks = tempfile::tempdir().unwrap();
+    let bin = marker_single(mocks.path());
+    let dir = tempfile::tempdir().unwrap();
+    let pdf = dir.path().join("doc.pdf");
+    write(&pdf, "%PDF-1.4");
+
+    run_ok(&[path_str(&pdf), "--marker-bin", path_str(&bin)]);
+    assert_eq!(read(&dir.path().join("doc_processed.json")), EXPECTED);
+    assert_eq!(files(dir.path()), ["doc.pdf", "doc_processed.json"]);
+}
+
+#[test]
+fn keep_intermediate_copies_the_json_next_to_the_output() {
+    let mocks = tempfile::tempdir().unwrap();
+    let bin = marker_single(mocks.path());
+    let dir = tempfile::tempdir().unwrap();
+    let pdf = dir.path().join("in/doc.pdf");
+    write(&pdf, "%PDF-1.4");
+    let out = dir.path().join("out");
+
+    run_ok(&[path_str(&pdf), "-o", path_str(&out), "--marker-bin", path_str(&bin), "--keep-intermediate"]);
+    assert_eq!(files(&out), ["doc.json", "doc_processed.json"]);
+    assert_eq!(read(&out.join("doc.json")), fixture());
+    assert_eq!(files(&dir.path().join("in")), ["doc.pdf"]);
+}
+
+#[test]
+fn marker_failure_is_reported_with_its_stderr() {
+    let mocks = tempfile::tempdir().unwrap();
+    let bin = mock(mocks.path(), "marker_single", "echo 'CUDA out of memory' >&2; exit 3");
+    let dir = tempfile::tempdir().unwrap();
+    let pdf = dir.path().join("doc.pdf");
+    write(&pdf, "%PDF-1.4");
+
+    let output = run(&[path_str(&pdf), "--marker-bin", path_str(&bin)]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("marker exited with status 3: CUDA out of memory"), "{}", stderr);
+    assert_eq!(files(dir.path()), ["doc.pdf"]);
+}
+
+#[test]
+fn directories_are_converted_with_the_batch_command() {
+    let mocks = tempfile::tempdir().unwrap();
+    let bin = marker_batch(mocks.path());
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("in");
+    let out = dir.path().join("out");
+    write(&input.join("a.pdf"), "%PDF-1.4 a");
+    write(&input.join("sub/b.pdf"), "%PDF-1.4 b");
+    // Same file name as a.pdf, so it needs a second batch
+    write(&input.join("sub/a.pdf"), "%PDF-1.4 sub a");
+
+    let output = run_ok(&[path_str(&input), "-o", path_str(&out), "--marker-batch-bin", path_str(&bin)]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("Unprocessed files"), "{}", stderr);
+
+    assert_eq!(read(&mocks.path().join("calls.log")).lines().count(), 2);
+    assert_eq!(files(&input), ["a.pdf", "sub/a.pdf", "sub/b.pdf"]);
+    assert_eq!(files(&out), ["a_processed.json", "sub/a_processed.json", "sub/b_processed.json"]);
+    for name in ["a_processed.json", "sub/a_processed.json", "sub/b_processed.json"] {
+        assert_eq!(read(&out.join(name)), EXPECTED);
+    }
+}
+
+#[test]
+fn a_failed_batch_marks_each_of_its_pdfs_unprocessed() {
+    let mocks = tempfile::tempdir().unwrap();
+    let bin = mock(mocks.path(), "marker", "echo 'model download failed' >&2; exit 1");
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("in");
+    write(&input.join("a.pdf"), "%PDF-1.4 a");
+    write(&input.join("b.pdf"), "%PDF-1.4 b");
+
+    let output = run_ok(&[path_str(&input), "-o", path_str(&dir.path().join("out")), "--marker-batch-bin", path_str(&bin)]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let failures = stderr.lines().filter(|line| line.contains("model download failed") && line.contains(".pdf: ")).count();
+    assert_eq!(failures, 2, "{}", stderr);
+}
+
+#[test]
+fn identical_pdfs_are_converted_once() {
+    let mocks = tempfile::tempdir().unwrap();
+    let bin = marker_batch(mocks.path());
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("in");
+    let out = dir.path().join("out");
+    write(&input.join("a.pdf"), "%PDF-1.4 same");
+    // Same name and contents: without deduplication it would need a second batch
+    write(&input.join("sub/a.pdf"), "%PDF-1.4 same");
+    write(&input.join("sub/b.pdf"), "%PDF-1.4 same");
+
+    let output = run_ok(&[
+        path_str(&input),
+        "-o",
+        path_str(&out),
+        "--marker-batch-bin",
+        path_str(&bin),
+        "--keep-intermediate",
+        "--stats",
+    ]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let original = input.canonicalize().unwrap().join("a.pdf");
+    assert_eq!(stderr.matches(&format!("Skipped duplicate of: {}", original.display())).count(), 2, "{}", stderr);
+
+    let calls = read(&mocks.path().join("calls.log"));
+    assert_eq!(calls.lines().count(), 1, "{}", calls);
+    assert_eq!(
+        files(&out),
+        [
+            "_summary_stats.json",
+            "a.json",
+            "a_processed.json",
+            "a_stats.json",
+            "sub/a.json",
+            "sub/a_processed.json",
+            "sub/a_stats.json",
+            "sub/b.json",
+            "sub/b_processed.json",
+            "sub/b_stats.json",
+        ]
+    );
+    assert_eq!(read(&out.join("sub/b_processed.json")), EXPECTED);
+    let stats: serde_json::Value = serde_json::from_str(&read(&out.join("sub/b_stats.json"))).unwrap();
+    assert!(stats["input_file"].as_str().unwrap().ends_with("b.pdf"));
+
+    let summary: serde_json::Value = serde_json::from_str(&read(&out.join("_summary_stats.json"))).unwrap();
+    assert_eq!(summary["files_processed"], 1);
+    assert_eq!(summary["files_skipped_duplicate"], 2);
+    assert_eq!(summary["files_failed"], 0);
+}
+
+#[test]
+fn no_dedup_converts_every_pdf() {
+    let mocks = tempfile::tempdir().unwrap();
+    let bin = marker_batch(mocks.path());
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("in");
+    write(&input.join("a.pdf"), "%PDF-1.4 same");
+    write(&input.join("sub/a.pdf"), "%PDF-1.4 same");
+
+    let output = run_ok(&[
+        path_str(&input),
+        "-o",
+        path_str(&dir.path().join("out")),
+        "--marker-batch-bin",
+        path_str(&bin),
+        "--no-dedup",
+    ]);
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("Skipped duplicate"));
+    assert_eq!(read(&mocks.path().join("calls.log")).lines().count(), 2);
+}
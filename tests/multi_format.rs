@@ -0,0 +1,74 @@
+mod common;
+
+use common::{marker_json, path_str, run, run_ok, write};
+use std::fs;
+use std::path::Path;
+
+const FORMATS: [(&str, &str); 5] = [
+    ("json", "json"),
+    ("text", "txt"),
+    ("markdown", "md"),
+    ("toml", "toml"),
+    ("parquet", "parquet"),
+];
+
+fn fixture(dir: &Path) -> std::path::PathBuf {
+    let input = dir.join("doc.json");
+    write(
+        &input,
+        &marker_json(&[
+            ("SectionHeader", "<h2>Results</h2>"),
+            ("Text", "<p>All samples passed.</p>"),
+            ("Table", "<table><tr><th>Sample</th><th>Result</th></tr><tr><td>A</td><td>pass</td></tr></table>"),
+            ("ListItem", "<li>Repeat in spring</li>"),
+        ]),
+    );
+    input
+}
+
+#[test]
+fn every_format_combination_matches_single_format_runs() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = fixture(dir.path());
+
+    // Reference output of each format written on its own
+    let single = dir.path().join("single");
+    for (format, extension) in FORMATS {
+        run_ok(&[path_str(&input), "-o", path_str(&single), "--output-format", format]);
+        assert!(single.join(format!("doc_processed.{}", extension)).is_file());
+    }
+
+    for mask in 1..(1u32 << FORMATS.len()) {
+        let selected: Vec<(&str, &str)> = FORMATS
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| mask & (1 << index) != 0)
+            .map(|(_, format)| *format)
+            .collect();
+        let list: Vec<&str> = selected.iter().map(|(format, _)| *format).collect();
+
+        let out = dir.path().join(format!("multi_{}", mask));
+        run_ok(&[path_str(&input), "-o", path_str(&out), "--multi-format", &list.join(",")]);
+
+        let written = fs::read_dir(&out).unwrap().count();
+        assert_eq!(written, selected.len(), "{:?}", list);
+        for (_, extension) in &selected {
+            let name = format!("doc_processed.{}", extension);
+            assert_eq!(
+                fs::read(out.join(&name)).unwrap(),
+                fs::read(single.join(&name)).unwrap(),
+                "{} differs for --multi-format {}",
+                name,
+                list.join(",")
+            );
+        }
+    }
+}
+
+#[test]
+fn multi_format_conflicts_with_output_format() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = fixture(dir.path());
+    let output = run(&[path_str(&input), "--multi-format", "json,text", "--output-format", "markdown"]);
+    assert_eq!(output.status.code(), Some(2));
+}
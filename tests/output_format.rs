@@ -0,0 +1,153 @@
+mod common;
+
+use common::{marker_json, path_str, read, run_ok, write};
+use std::path::{Path, PathBuf};
+
+const EXPECTED_TEXT: &str = "\
+Field Report
+Results
+All samples passed & logged.
+Sample Result A pass B|C fail
+Repeat in spring
+Archive the data
+Notes
+Done.
+";
+
+const EXPECTED_MARKDOWN: &str = "\
+# Field Report
+
+## Results
+
+All samples passed & logged.
+
+| Sample | Result |
+| --- | --- |
+| A | pass |
+| B\\|C | fail |
+
+- Repeat in spring
+
+- Archive the data
+
+### Notes
+
+Done.
+";
+
+const EXPECTED_JSON: &str = r#"[
+  {
+    "id": "/page/0/Title/0",
+    "block_type": "Title",
+    "html": "<h1>Field Report</h1>",
+    "text": "Field Report"
+  },
+  {
+    "id": "/page/0/SectionHeader/1",
+    "block_type": "SectionHeader",
+    "html": "<h2>Results</h2>",
+    "text": "Results"
+  },
+  {
+    "id": "/page/0/Text/2",
+    "block_type": "Text",
+    "html": "<p>All <b>samples</b> passed &amp; logged.</p>",
+    "text": "All samples passed & logged."
+  },
+  {
+    "id": "/page/0/Table/3",
+    "block_type": "Table",
+    "html": "<table><tr><th>Sample</th><th>Result</th></tr><tr><td>A</td><td>pass</td></tr><tr><td>B|C</td><td>fail</td></tr></table>",
+    "text": "Sample Result A pass B|C fail"
+  },
+  {
+    "id": "/page/0/ListItem/4",
+    "block_type": "ListItem",
+    "html": "<li>Repeat in spring</li>",
+    "text": "Repeat in spring"
+  },
+  {
+    "id": "/page/0/ListItem/5",
+    "block_type": "ListItem",
+    "html": "<li>Archive the data</li>",
+    "text": "Archive the data"
+  },
+  {
+    "id": "/page/0/SectionHeader/6",
+    "block_type": "SectionHeader",
+    "html": "<h3>Notes</h3>",
+    "text": "Notes"
+  },
+  {
+    "id": "/page/0/Text/7",
+    "block_type": "Text",
+    "html": "<p>Done.</p>",
+    "text": "Done."
+  }
+]"#;
+
+const FORMATS: [(&str, &str, &str); 3] = [
+    ("json", "json", EXPECTED_JSON),
+    ("text", "txt", EXPECTED_TEXT),
+    ("markdown", "md", EXPECTED_MARKDOWN),
+];
+
+// Every block type the text and Markdown writers treat differently
+fn fixture(path: &Path) {
+    write(
+        path,
+        &marker_json(&[
+            ("Title", "<h1>Field Report</h1>"),
+            ("SectionHeader", "<h2>Results</h2>"),
+            ("Text", "<p>All <b>samples</b> passed &amp; logged.</p>"),
+            (
+                "Table",
+                "<table><tr><th>Sample</th><th>Result</th></tr><tr><td>A</td><td>pass</td></tr><tr><td>B|C</td><td>fail</td></tr></table>",
+            ),
+            ("ListItem", "<li>Repeat in spring</li>"),
+            ("ListItem", "<li>Archive the data</li>"),
+            ("SectionHeader", "<h3>Notes</h3>"),
+            ("Text", "<p>Done.</p>"),
+        ]),
+    );
+}
+
+fn output_path(dir: &Path, extension: &str) -> PathBuf {
+    dir.join(format!("doc_processed.{}", extension))
+}
+
+#[test]
+fn single_file_output_matches_byte_for_byte() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("doc.json");
+    fixture(&input);
+
+    for (format, extension, expected) in FORMATS {
+        let out = dir.path().join(format);
+        run_ok(&[path_str(&input), "-o", path_str(&out), "--format", format]);
+        assert_eq!(read(&output_path(&out, extension)), expected, "--format {}", format);
+    }
+}
+
+#[test]
+fn directory_output_matches_byte_for_byte() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("in");
+    fixture(&input.join("doc.json"));
+
+    for (format, extension, expected) in FORMATS {
+        let out = dir.path().join(format);
+        run_ok(&[path_str(&input), "-o", path_str(&out), "--output-format", format]);
+        assert_eq!(read(&output_path(&out, extension)), expected, "--output-format {}", format);
+    }
+}
+
+#[test]
+fn default_output_is_json_next_to_the_input() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("doc.json");
+    fixture(&input);
+
+    run_ok(&[path_str(&input)]);
+    assert_eq!(read(&output_path(dir.path(), "json")), EXPECTED_JSON);
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use common::{marker_json, path_str, run_ok, write};
+
+#[test]
+fn preview_stdout_is_only_json() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("doc.json");
+    write(
+        &input,
+        &marker_json(&[("SectionHeader", "<h1>Title</h1>"), ("Text", "<p>Body text.</p>"), ("Text", "<p>More.</p>")]),
+    );
+
+    let output = run_ok(&[path_str(&input), "--preview", "2", "--flag-punctuation-noise"]);
+    let preview: serde_json::Value = serde_json::from_slice(&output.stdout).expect("stdout is one JSON document");
+    let texts: Vec<&str> = preview.as_array().unwrap().iter().map(|block| block["text"].as_str().unwrap()).collect();
+    assert_eq!(texts, ["Title", "Body text."]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Processing JSON file"));
+}
+
+#[test]
+fn directory_preview_writes_nothing() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("in");
+    let output_dir = dir.path().join("out");
+    write(&input.join("a.json"), &marker_json(&[("Text", "<p>First</p>")]));
+    write(&input.join("sub/b.json"), &marker_json(&[("Text", "<p>Second</p>")]));
+
+    let output = run_ok(&[path_str(&input), "-o", path_str(&output_dir), "--preview", "5", "--stats"]);
+    assert!(!output_dir.exists());
+
+    // One JSON array per file, and nothing else, on stdout
+    let previews: Vec<serde_json::Value> = serde_json::Deserializer::from_slice(&output.stdout)
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .expect("stdout is a stream of JSON documents");
+    assert_eq!(previews.len(), 2);
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::{marker_json, path_str, read, run_ok, write};
+use scraper::{Html, Selector};
+use std::path::Path;
+
+const TABLE: &str = "<table><tr><th>Name</th><th>Year</th></tr><tr><td>A</td><td>1</td></tr></table>";
+const TABLE_CONTINUED: &str = "<table><tr><th>Name</th><th>Year</th></tr><tr><td>B</td><td>2</td></tr></table>";
+
+fn document() -> String {
+    marker_json(&[
+        ("PageHeader", "<p>Running head</p>"),
+        ("SectionHeader", "<h1>Results</h1>"),
+        ("Text", "<p>if a &lt;b&gt; c &amp; d</p>"),
+        ("Table", TABLE),
+        ("Table", TABLE_CONTINUED),
+    ])
+}
+
+fn generate_report(dir: &Path, extra_args: &[&str]) -> Html {
+    let input = dir.join("doc.json");
+    write(&input, &document());
+    let output_dir = dir.join("out");
+    let mut args = vec![path_str(&input), "-o", path_str(&output_dir), "--generate-report"];
+    args.extend(extra_args);
+    run_ok(&args);
+    Html::parse_document(&read(&output_dir.join("doc_report.html")))
+}
+
+fn texts(html: &Html, selector: &str) -> Vec<String> {
+    let selector = Selector::parse(selector).unwrap();
+    html.select(&selector)
+        .map(|element| element.text().collect::<String>())
+        .collect()
+}
+
+// The summary table value in the row labelled `label`
+fn summary_value(html: &Html, label: &str) -> String {
+    let row = Selector::parse("table.summary tr").unwrap();
+    let th = Selector::parse("th").unwrap();
+    let td = Selector::parse("td").unwrap();
+    html.select(&row)
+        .find(|row| row.select(&th).next().is_some_and(|th| th.text().collect::<String>() == label))
+        .and_then(|row| row.select(&td).next())
+        .map(|td| td.text().collect())
+        .unwrap_or_else(|| panic!("no summary row {:?}", label))
+}
+
+#[test]
+fn report_sections_describe_the_run() {
+    let dir = tempfile::tempdir().unwrap();
+    let html = generate_report(dir.path(), &[]);
+
+    assert_eq!(texts(&html, "h1"), [format!("Extraction report: {}", dir.path().join("doc.json").display())]);
+    assert_eq!(summary_value(&html, "Input blocks"), "5");
+    assert_eq!(summary_value(&html, "Output blocks"), "4");
+    assert!(summary_value(&html, "Processing time").ends_with(" ms"));
+
+    // One histogram bar per output block type
+    assert_eq!(html.select(&Selector::parse("svg.histogram rect").unwrap()).count(), 3);
+
+    let page_cells = texts(&html, "table.pages td");
+    assert_eq!(page_cells, ["0", "4", "15", "2", "no", "Results"]);
+
+    // Block text is escaped, so markup in it stays text
+    let cards = texts(&html, ".card p");
+    assert!(cards.contains(&"if a <b> c & d".to_string()), "{:?}", cards);
+    assert!(html.select(&Selector::parse(".card p b").unwrap()).next().is_none());
+
+    assert_eq!(texts(&html, "table.unprocessed td.id"), ["/page/0/PageHeader/0"]);
+    assert_eq!(texts(&html, "table.unprocessed td:last-child"), ["Page header or footer"]);
+}
+
+#[test]
+fn tables_are_reported_as_merged_only_when_merging() {
+    let dir = tempfile::tempdir().unwrap();
+    let html = generate_report(dir.path(), &["--merge-split-tables"]);
+    assert_eq!(summary_value(&html, "Output blocks"), "3");
+    assert_eq!(
+        texts(&html, "table.unprocessed td.id"),
+        ["/page/0/PageHeader/0", "/page/0/Table/4"]
+    );
+    assert_eq!(
+        texts(&html, "table.unprocessed td:last-child"),
+        ["Page header or footer", "Merged into the preceding table"]
+    );
+}
@@ -0,0 +1,124 @@
+// Files are streamed page by page unless a pass or output needs the whole
+// document; both paths must write the same bytes
+mod common;
+
+use common::{path_str, read, run, run_ok, write};
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+
+fn page(number: usize, blocks: &[(&str, &str)]) -> serde_json::Value {
+    let children: Vec<serde_json::Value> = blocks
+        .iter()
+        .enumerate()
+        .map(|(index, (block_type, html))| {
+            json!({
+                "id": format!("/page/{}/{}/{}", number, block_type, index),
+                "block_type": block_type,
+                "html": html,
+                "images": {"img.jpeg": "/9j/4AAQSkZJRg=="},
+            })
+        })
+        .collect();
+    json!({
+        "id": format!("/page/{}/Page/0", number),
+        "block_type": "Page",
+        "html": "",
+        "children": children,
+    })
+}
+
+// Three pages, one of them empty, with noise for the per-block passes
+fn fixture(path: &Path) {
+    let document = json!({
+        "metadata": {"pages": 3},
+        "children": [
+            page(0, &[
+                ("PageHeader", "<p>Running head</p>"),
+                ("SectionHeader", "<h1>Introduction</h1>"),
+                ("Text", "<p>Fish &amp; chips\u{7}.</p>"),
+                ("Text", "<p>#### ~~~~ #### ~~~~ ####</p>"),
+            ]),
+            page(1, &[]),
+            page(2, &[
+                ("SectionHeader", "<h4>Details</h4>"),
+                ("Text", "<p>Second page.</p>"),
+                ("PageFooter", "<p>3</p>"),
+            ]),
+        ],
+    });
+    write(path, &serde_json::to_string_pretty(&document).unwrap());
+}
+
+// Output files of a run with `args`, read back by name
+fn outputs(dir: &Path, input: &Path, args: &[&str]) -> Vec<(String, String)> {
+    let out = dir.join("out");
+    let _ = fs::remove_dir_all(&out);
+    let mut all_args = vec![path_str(input), "-o", path_str(&out), "--multi-format", "json,text", "--stats"];
+    all_args.extend(args);
+    run_ok(&all_args);
+
+    let mut files: Vec<(String, String)> = fs::read_dir(&out)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| !path.to_string_lossy().ends_with(".html"))
+        .map(|path| {
+            let mut contents = read(&path);
+            if path.to_string_lossy().ends_with("_stats.json") {
+                let mut stats: serde_json::Value = serde_json::from_str(&contents).unwrap();
+                stats["processing_time_ms"] = json!(0);
+                contents = stats.to_string();
+            }
+            (path.file_name().unwrap().to_string_lossy().into_owned(), contents)
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn streamed_output_matches_whole_document_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("doc.json");
+    fixture(&input);
+
+    for passes in [
+        &[][..],
+        &["--drop-punctuation-noise", "--strip-control-chars", "--max-heading-depth", "2"],
+        &["--base64-encode-text"],
+    ] {
+        let streamed = outputs(dir.path(), &input, passes);
+        // The HTML report needs the whole document, so the file is read whole
+        let whole = outputs(dir.path(), &input, &[passes, &["--generate-report"]].concat());
+        let names: Vec<&str> = streamed.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["doc_processed.json", "doc_processed.txt", "doc_stats.json"]);
+        assert_eq!(streamed, whole, "{:?}", passes);
+    }
+}
+
+#[test]
+fn empty_documents_stream_to_empty_outputs() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("doc.json");
+    write(&input, r#"{"children": []}"#);
+
+    let files = outputs(dir.path(), &input, &[]);
+    assert_eq!(files[0], ("doc_processed.json".to_string(), "[]".to_string()));
+    assert_eq!(files[1], ("doc_processed.txt".to_string(), "\n".to_string()));
+}
+
+#[test]
+fn invalid_json_leaves_no_partial_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("doc.json");
+    fixture(&input);
+    // Cut off in the middle of the last page
+    let contents = read(&input);
+    write(&input, &contents[..contents.len() - 40]);
+    let out = dir.path().join("out");
+
+    let output = run(&[path_str(&input), "-o", path_str(&out)]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid JSON schema"));
+    assert!(!out.exists() || fs::read_dir(&out).unwrap().next().is_none());
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::{marker_json, path_str, read, run_ok, write};
+use std::fs;
+
+#[test]
+fn toml_output_reads_back_as_processed_blocks() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("doc.json");
+    write(
+        &input,
+        &marker_json(&[
+            ("SectionHeader", "<h1>Title</h1>"),
+            ("Text", "<p>Original &amp; text</p>"),
+            ("PageHeader", "<p>Running header</p>"),
+        ]),
+    );
+    let first = dir.path().join("first");
+    run_ok(&[path_str(&input), "-o", path_str(&first), "--multi-format", "json,toml"]);
+
+    // Reading the TOML back gives the same blocks as the JSON output
+    let toml_path = first.join("doc_processed.toml");
+    let second = dir.path().join("second");
+    run_ok(&[path_str(&toml_path), "-o", path_str(&second), "--multi-format", "json,toml"]);
+    assert_eq!(
+        read(&second.join("doc_processed_processed.json")),
+        read(&first.join("doc_processed.json"))
+    );
+    assert_eq!(read(&second.join("doc_processed_processed.toml")), read(&toml_path));
+
+    // Text edited in the TOML is kept rather than rebuilt from the html
+    let edited = read(&toml_path).replace("text = \"Original & text\"", "text = \"Edited text\"");
+    assert_ne!(edited, read(&toml_path));
+    fs::write(&toml_path, edited).unwrap();
+    let third = dir.path().join("third");
+    run_ok(&[path_str(&toml_path), "-o", path_str(&third), "--output-format", "text"]);
+    assert_eq!(read(&third.join("doc_processed_processed.txt")).trim(), "Title\nEdited text");
+}
+
+#[test]
+fn processing_flags_apply_to_already_processed_input() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("doc.json");
+    write(
+        &input,
+        &marker_json(&[
+            ("SectionHeader", "<h1>Title</h1>"),
+            ("Text", "<p>Bell\u{7}here</p>"),
+            ("Table", "<table><tr><td>1</td></tr></table>"),
+        ]),
+    );
+    let first = dir.path().join("first");
+    run_ok(&[path_str(&input), "-o", path_str(&first), "--multi-format", "json,toml"]);
+
+    for processed in ["doc_processed.toml", "doc_processed.json"] {
+        let out = dir.path().join(processed.replace('.', "_"));
+        run_ok(&[
+            path_str(&first.join(processed)),
+            "-o",
+            path_str(&out),
+            "--output-format",
+            "text",
+            "--strip-control-chars",
+            "--exclude-types",
+            "Table",
+        ]);
+        assert_eq!(read(&out.join("doc_processed_processed.txt")), "Title\nBell here\n", "{}", processed);
+    }
+}
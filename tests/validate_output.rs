@@ -0,0 +1,41 @@
+mod common;
+
+use common::{path_str, run, write};
+
+#[test]
+fn valid_output_exits_with_zero() {
+    let dir = tempfile::tempdir().unwrap();
+    let processed = dir.path().join("doc_processed.json");
+    write(&processed, r#"[{"id": "/page/0/Text/0", "block_type": "Text", "html": "<p>a</p>", "text": "a"}]"#);
+
+    let output = run(&["validate-output", path_str(&processed)]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("is valid"));
+}
+
+#[test]
+fn invalid_output_exits_with_one_and_reports_every_violation() {
+    let dir = tempfile::tempdir().unwrap();
+    let processed = dir.path().join("doc_processed.json");
+    write(&processed, r#"[{"id": 7, "block_type": "Text", "html": ""}, {"id": "b", "block_type": "Text", "html": "", "text": ""}]"#);
+
+    let output = run(&["validate-output", path_str(&processed)]);
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("/0/id: expected string, found number"), "{}", stdout);
+    assert!(stdout.contains("/0: missing required field 'text'"), "{}", stdout);
+    assert!(stdout.contains("2 violation(s) found"), "{}", stdout);
+}
+
+#[test]
+fn custom_schema_is_used() {
+    let dir = tempfile::tempdir().unwrap();
+    let processed = dir.path().join("doc_processed.json");
+    let schema = dir.path().join("schema.json");
+    write(&processed, r#"[{"id": "a", "block_type": "Picture", "html": "", "text": ""}]"#);
+    write(&schema, r#"{"items": {"properties": {"block_type": {"enum": ["Text"]}}}}"#);
+
+    assert_eq!(run(&["validate-output", path_str(&processed)]).status.code(), Some(0));
+    let output = run(&["validate-output", path_str(&processed), "--schema", path_str(&schema)]);
+    assert_eq!(output.status.code(), Some(1));
+}